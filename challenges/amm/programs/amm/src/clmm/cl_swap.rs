@@ -0,0 +1,251 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{transfer_checked, Mint, TokenAccount, TokenInterface, TransferChecked},
+};
+
+use crate::{
+    assert_non_zero, assert_not_expired,
+    clmm::math::{add_liquidity_delta, sqrt_price_at_tick, swap_step, tick_at_sqrt_price},
+    error::AmmError,
+    helpers::{amount_after_fee, amount_before_fee},
+    ClPool, ClSwapped, Tick, CL_POOL_SEED,
+};
+
+#[derive(Accounts)]
+pub struct ClSwap<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+    pub mint_x: Box<InterfaceAccount<'info, Mint>>,
+    pub mint_y: Box<InterfaceAccount<'info, Mint>>,
+    #[account(
+        mut,
+        has_one = mint_x,
+        has_one = mint_y,
+        seeds = [
+            CL_POOL_SEED.as_ref(),
+            mint_x.key().as_ref(),
+            mint_y.key().as_ref(),
+            pool.tick_spacing.to_le_bytes().as_ref()
+        ],
+        bump = pool.bump
+    )]
+    pub pool: Box<Account<'info, ClPool>>,
+    #[account(
+        mut,
+        associated_token::mint = mint_x,
+        associated_token::authority = pool,
+        associated_token::token_program = token_program,
+    )]
+    pub vault_x: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        mut,
+        associated_token::mint = mint_y,
+        associated_token::authority = pool,
+        associated_token::token_program = token_program,
+    )]
+    pub vault_y: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        init_if_needed,
+        payer = user,
+        associated_token::mint = mint_x,
+        associated_token::authority = user,
+        associated_token::token_program = token_program,
+    )]
+    pub user_x: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        init_if_needed,
+        payer = user,
+        associated_token::mint = mint_y,
+        associated_token::authority = user,
+        associated_token::token_program = token_program,
+    )]
+    pub user_y: Box<InterfaceAccount<'info, TokenAccount>>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> ClSwap<'info> {
+    // Swap `amount` of X (or Y), crossing the initialized ticks passed in `remaining_accounts`
+    // in the order the price reaches them. Stops early once the price runs out of range, in
+    // which case only the part of `amount` that was used is taken.
+    pub fn cl_swap(
+        &mut self,
+        remaining_accounts: &'info [AccountInfo<'info>],
+        program_id: &Pubkey,
+        is_x: bool,
+        amount: u64,
+        min: u64,
+        expiration: i64,
+    ) -> Result<()> {
+        assert_not_expired!(expiration);
+        assert_non_zero!([amount]);
+
+        let (mint_in, mint_out) = match is_x {
+            true => (self.mint_x.to_account_info(), self.mint_y.to_account_info()),
+            false => (self.mint_y.to_account_info(), self.mint_x.to_account_info()),
+        };
+
+        // Only what reaches the vault after the transfer fee is priced
+        let amount_in = amount_after_fee(&mint_in, amount)?;
+        assert_non_zero!([amount_in]);
+
+        // X in moves the price down, Y in moves it up
+        let (min_tick, max_tick) = self.pool.tick_bounds();
+        let mut ticks = remaining_accounts.iter();
+        let mut remaining = amount_in;
+        let (mut amount_out, mut fee) = (0u64, 0u64);
+
+        while remaining > 0 {
+            let next = self
+                .pool
+                .next_initialized_tick(self.pool.tick_current, is_x);
+            let target_tick = match (next, is_x) {
+                (Some(tick), _) => tick,
+                (None, true) => min_tick,
+                (None, false) => max_tick,
+            };
+            let target = sqrt_price_at_tick(target_tick)?;
+
+            let step = swap_step(
+                self.pool.sqrt_price,
+                target,
+                self.pool.liquidity,
+                remaining,
+                self.pool.fee,
+            )?;
+            remaining -= step.amount_in + step.fee;
+            amount_out = amount_out
+                .checked_add(step.amount_out)
+                .ok_or(AmmError::Overflow)?;
+            fee += step.fee;
+            self.pool.accrue_fee(is_x, step.fee)?;
+            self.pool.sqrt_price = step.sqrt_price;
+
+            if step.sqrt_price != target {
+                self.pool.tick_current = tick_at_sqrt_price(step.sqrt_price)?;
+                break;
+            }
+
+            // Past the last initialized tick there is no liquidity left to swap against
+            if next.is_none() {
+                self.pool.tick_current = target_tick;
+                break;
+            }
+
+            let info = ticks.next().ok_or(AmmError::InvalidTickAccount)?;
+            let mut tick = Account::<Tick>::try_from(info)?;
+            require!(
+                tick.pool == self.pool.key() && tick.index == target_tick,
+                AmmError::InvalidTickAccount
+            );
+            let liquidity_net =
+                tick.cross(self.pool.fee_growth_global_x, self.pool.fee_growth_global_y);
+            tick.exit(program_id)?;
+
+            self.pool.liquidity = match is_x {
+                true => add_liquidity_delta(self.pool.liquidity, -liquidity_net)?,
+                false => add_liquidity_delta(self.pool.liquidity, liquidity_net)?,
+            };
+            self.pool.tick_current = match is_x {
+                true => target_tick - 1,
+                false => target_tick,
+            };
+        }
+
+        let used = amount_in - remaining;
+        assert_non_zero!([used, amount_out]);
+
+        // Check for slippage on what the user actually receives
+        let received = amount_after_fee(&mint_out, amount_out)?;
+        require!(received >= min, AmmError::SlippageExceeded);
+
+        // A partial fill only sends what it takes for `used` to arrive
+        let gross_in = match remaining {
+            0 => amount,
+            _ => amount_before_fee(&mint_in, used)?,
+        };
+        self.deposit_token(is_x, gross_in)?;
+        self.withdraw_token(is_x, amount_out)?;
+
+        emit!(ClSwapped {
+            pool: self.pool.key(),
+            user: self.user.key(),
+            is_x,
+            amount_in: used,
+            amount_out,
+            fee,
+            sqrt_price: self.pool.sqrt_price,
+            tick: self.pool.tick_current,
+        });
+        Ok(())
+    }
+
+    pub fn deposit_token(&self, is_x: bool, amount: u64) -> Result<()> {
+        let (from, to, mint, decimals) = match is_x {
+            true => (
+                self.user_x.to_account_info(),
+                self.vault_x.to_account_info(),
+                self.mint_x.to_account_info(),
+                self.mint_x.decimals,
+            ),
+            false => (
+                self.user_y.to_account_info(),
+                self.vault_y.to_account_info(),
+                self.mint_y.to_account_info(),
+                self.mint_y.decimals,
+            ),
+        };
+
+        let cpi_account = TransferChecked {
+            from,
+            to,
+            authority: self.user.to_account_info(),
+            mint,
+        };
+        let cpi_context = CpiContext::new(self.token_program.to_account_info(), cpi_account);
+        transfer_checked(cpi_context, amount, decimals)
+    }
+
+    // Pays out the other token from `deposit_token`
+    pub fn withdraw_token(&self, is_x: bool, amount: u64) -> Result<()> {
+        let (from, to, mint, decimals) = match is_x {
+            true => (
+                self.vault_y.to_account_info(),
+                self.user_y.to_account_info(),
+                self.mint_y.to_account_info(),
+                self.mint_y.decimals,
+            ),
+            false => (
+                self.vault_x.to_account_info(),
+                self.user_x.to_account_info(),
+                self.mint_x.to_account_info(),
+                self.mint_x.decimals,
+            ),
+        };
+
+        let cpi_account = TransferChecked {
+            from,
+            to,
+            authority: self.pool.to_account_info(),
+            mint,
+        };
+
+        let spacing = self.pool.tick_spacing.to_le_bytes();
+        let seeds = &[
+            &CL_POOL_SEED.as_ref()[..],
+            self.pool.mint_x.as_ref(),
+            self.pool.mint_y.as_ref(),
+            spacing.as_ref(),
+            &[self.pool.bump],
+        ];
+        let signer_seeds = &[&seeds[..]];
+        let cpi_context = CpiContext::new_with_signer(
+            self.token_program.to_account_info(),
+            cpi_account,
+            signer_seeds,
+        );
+        transfer_checked(cpi_context, amount, decimals)
+    }
+}
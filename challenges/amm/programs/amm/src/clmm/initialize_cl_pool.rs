@@ -0,0 +1,104 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{Mint, TokenAccount, TokenInterface},
+};
+
+use crate::{
+    clmm::math::{sqrt_price_at_tick, tick_at_sqrt_price},
+    error::AmmError,
+    helpers::assert_supported_mint,
+    ClPool, ClPoolInitialized, CL_POOL_SEED, MAX_TICK_SPACING,
+};
+
+#[derive(Accounts)]
+#[instruction(tick_spacing: u16)]
+pub struct InitializeClPool<'info> {
+    #[account(mut)]
+    pub initializer: Signer<'info>,
+    pub mint_x: Box<InterfaceAccount<'info, Mint>>,
+    // Sorted so each pair and spacing has a single pool
+    #[account(constraint = mint_x.key() < mint_y.key() @ AmmError::UnsortedMints)]
+    pub mint_y: Box<InterfaceAccount<'info, Mint>>,
+    #[account(
+        init,
+        payer = initializer,
+        seeds = [
+            CL_POOL_SEED.as_ref(),
+            mint_x.key().as_ref(),
+            mint_y.key().as_ref(),
+            tick_spacing.to_le_bytes().as_ref()
+        ],
+        bump,
+        space = ClPool::LEN
+    )]
+    pub pool: Box<Account<'info, ClPool>>,
+    #[account(
+        init,
+        payer = initializer,
+        associated_token::mint = mint_x,
+        associated_token::authority = pool,
+        associated_token::token_program = token_program,
+    )]
+    pub vault_x: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        init,
+        payer = initializer,
+        associated_token::mint = mint_y,
+        associated_token::authority = pool,
+        associated_token::token_program = token_program,
+    )]
+    pub vault_y: Box<InterfaceAccount<'info, TokenAccount>>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> InitializeClPool<'info> {
+    pub fn initialize_cl_pool(
+        &mut self,
+        tick_spacing: u16,
+        fee: u16,
+        sqrt_price: u128,
+        bump: u8,
+    ) -> Result<()> {
+        require!(
+            tick_spacing > 0 && tick_spacing <= MAX_TICK_SPACING,
+            AmmError::InvalidTickSpacing
+        );
+        // The whole input can't go to fees
+        require!(fee < 10000, AmmError::InvalidFee);
+        assert_supported_mint(&self.mint_x.to_account_info())?;
+        assert_supported_mint(&self.mint_y.to_account_info())?;
+
+        let tick = tick_at_sqrt_price(sqrt_price)?;
+        self.pool.init(
+            self.mint_x.key(),
+            self.mint_y.key(),
+            tick_spacing,
+            fee,
+            sqrt_price,
+            tick,
+            bump,
+        );
+
+        // Start inside the range positions can cover so swaps can reach every position
+        let (min, max) = self.pool.tick_bounds();
+        require!(
+            sqrt_price_at_tick(min)? <= sqrt_price && sqrt_price <= sqrt_price_at_tick(max)?,
+            AmmError::InvalidSqrtPrice
+        );
+
+        emit!(ClPoolInitialized {
+            pool: self.pool.key(),
+            initializer: self.initializer.key(),
+            mint_x: self.mint_x.key(),
+            mint_y: self.mint_y.key(),
+            tick_spacing,
+            fee,
+            sqrt_price,
+            tick,
+        });
+        Ok(())
+    }
+}
@@ -0,0 +1,219 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    error::AmmError,
+    helpers::{mul_div, mul_div_ceil, widening_mul},
+    MAX_TICK, MIN_TICK,
+};
+
+// Prices are kept as sqrt(price) in Q64.64, price being Y per X
+pub const ONE: u128 = 1 << 64;
+
+// Q0.128 sqrt(1.0001^-2^i) for each bit i of the tick above the lowest one
+const TICK_FACTORS: [(u32, u128); 19] = [
+    (0x2, 0xfff97272373d413259a46990580e213a),
+    (0x4, 0xfff2e50f5f656932ef12357cf3c7fdcc),
+    (0x8, 0xffe5caca7e10e4e61c3624eaa0941cd0),
+    (0x10, 0xffcb9843d60f6159c9db58835c926644),
+    (0x20, 0xff973b41fa98c081472e6896dfb254c0),
+    (0x40, 0xff2ea16466c96a3843ec78b326b52861),
+    (0x80, 0xfe5dee046a99a2a811c461f1969c3053),
+    (0x100, 0xfcbe86c7900a88aedcffc83b479aa3a4),
+    (0x200, 0xf987a7253ac413176f2b074cf7815e54),
+    (0x400, 0xf3392b0822b70005940c7a398e4b70f3),
+    (0x800, 0xe7159475a2c29b7443b29c7fa6e889d9),
+    (0x1000, 0xd097f3bdfd2022b8845ad8f792aa5825),
+    (0x2000, 0xa9f746462d870fdf8a65dc1f90e061e5),
+    (0x4000, 0x70d869a156d2a1b890bb3df62baf32f7),
+    (0x8000, 0x31be135f97d08fd981231505542fcfa6),
+    (0x10000, 0x9aa508b5b7a84e1c677de54f3e99bc9),
+    (0x20000, 0x5d6af8dedb81196699c329225ee604),
+    (0x40000, 0x2216e584f5fa1ea926041bedfe98),
+    (0x80000, 0x48a170391f7dc42444e8fa2),
+];
+
+// sqrt(1.0001^tick) as Q64.64
+pub fn sqrt_price_at_tick(tick: i32) -> Result<u128> {
+    require!((MIN_TICK..=MAX_TICK).contains(&tick), AmmError::InvalidTick);
+
+    // Build sqrt(1.0001^-|tick|) below one from the bits of |tick|, starting just under 1.0
+    let abs = tick.unsigned_abs();
+    let mut ratio = match abs & 1 {
+        1 => 0xfffcb933bd6fad37aa2d162d1a594001,
+        _ => u128::MAX,
+    };
+    for (bit, factor) in TICK_FACTORS {
+        if abs & bit != 0 {
+            ratio = widening_mul(ratio, factor).0;
+        }
+    }
+
+    // Invert for positive ticks, then drop from Q0.128 to Q64.64
+    match tick > 0 {
+        true => mul_div(1 << 96, 1 << 96, ratio).ok_or(AmmError::Overflow.into()),
+        false => Ok((ratio >> 64) + (ratio as u64 != 0) as u128),
+    }
+}
+
+pub fn min_sqrt_price() -> u128 {
+    sqrt_price_at_tick(MIN_TICK).unwrap()
+}
+
+pub fn max_sqrt_price() -> u128 {
+    sqrt_price_at_tick(MAX_TICK).unwrap()
+}
+
+// Greatest tick whose sqrt price is at or below `sqrt_price`
+pub fn tick_at_sqrt_price(sqrt_price: u128) -> Result<i32> {
+    require!(
+        (min_sqrt_price()..=max_sqrt_price()).contains(&sqrt_price),
+        AmmError::InvalidSqrtPrice
+    );
+
+    let (mut low, mut high) = (MIN_TICK, MAX_TICK);
+    while low < high {
+        let mid = low + (high - low + 1) / 2;
+        match sqrt_price_at_tick(mid)? <= sqrt_price {
+            true => low = mid,
+            false => high = mid - 1,
+        }
+    }
+    Ok(low)
+}
+
+// X held by `liquidity` between two sqrt prices: L * (b - a) / (a * b)
+pub fn amount_x_delta(a: u128, b: u128, liquidity: u128, round_up: bool) -> Result<u128> {
+    let (a, b) = (a.min(b), a.max(b));
+    require!(a > 0, AmmError::InvalidSqrtPrice);
+    let amount = match round_up {
+        true => mul_div_ceil(liquidity, b - a, b).and_then(|n| mul_div_ceil(n, ONE, a)),
+        false => mul_div(liquidity, b - a, b).and_then(|n| mul_div(n, ONE, a)),
+    };
+    amount.ok_or(AmmError::Overflow.into())
+}
+
+// Y held by `liquidity` between two sqrt prices: L * (b - a)
+pub fn amount_y_delta(a: u128, b: u128, liquidity: u128, round_up: bool) -> Result<u128> {
+    let (a, b) = (a.min(b), a.max(b));
+    let amount = match round_up {
+        true => mul_div_ceil(liquidity, b - a, ONE),
+        false => mul_div(liquidity, b - a, ONE),
+    };
+    amount.ok_or(AmmError::Overflow.into())
+}
+
+// Tokens `liquidity` is worth over [lower, upper] at the current price, rounded towards the pool
+pub fn amounts_for_liquidity(
+    sqrt_price: u128,
+    lower: u128,
+    upper: u128,
+    liquidity: u128,
+    round_up: bool,
+) -> Result<(u64, u64)> {
+    let (x, y) = match sqrt_price {
+        p if p <= lower => (amount_x_delta(lower, upper, liquidity, round_up)?, 0),
+        p if p < upper => (
+            amount_x_delta(p, upper, liquidity, round_up)?,
+            amount_y_delta(lower, p, liquidity, round_up)?,
+        ),
+        _ => (0, amount_y_delta(lower, upper, liquidity, round_up)?),
+    };
+    Ok((
+        u64::try_from(x).map_err(|_| AmmError::Overflow)?,
+        u64::try_from(y).map_err(|_| AmmError::Overflow)?,
+    ))
+}
+
+// Sqrt price once `amount` of X is added, rounded up so the pool never pays out too much
+pub fn sqrt_price_after_x_in(sqrt_price: u128, liquidity: u128, amount: u64) -> Result<u128> {
+    if amount == 0 {
+        return Ok(sqrt_price);
+    }
+    let denominator = mul_div(amount as u128, sqrt_price, ONE)
+        .and_then(|n| n.checked_add(liquidity))
+        .ok_or(AmmError::Overflow)?;
+    mul_div_ceil(liquidity, sqrt_price, denominator).ok_or(AmmError::Overflow.into())
+}
+
+// Sqrt price once `amount` of Y is added, rounded down so the pool never pays out too much
+pub fn sqrt_price_after_y_in(sqrt_price: u128, liquidity: u128, amount: u64) -> Result<u128> {
+    let delta = mul_div(amount as u128, ONE, liquidity).ok_or(AmmError::Overflow)?;
+    sqrt_price
+        .checked_add(delta)
+        .ok_or(AmmError::Overflow.into())
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SwapStep {
+    pub sqrt_price: u128,
+    pub amount_in: u64, // Net of the fee
+    pub amount_out: u64,
+    pub fee: u64,
+}
+
+// Swap as much of `amount_remaining` as fits between the current price and `target`
+// with constant `liquidity`. X in moves the price down, Y in moves it up.
+pub fn swap_step(
+    sqrt_price: u128,
+    target: u128,
+    liquidity: u128,
+    amount_remaining: u64,
+    fee: u16,
+) -> Result<SwapStep> {
+    require!(fee < 10000, AmmError::InvalidFee);
+    let x_in = target <= sqrt_price;
+    let less_fee = (amount_remaining as u128 * (10000 - fee as u128) / 10000) as u64;
+
+    let max_in = match x_in {
+        true => amount_x_delta(target, sqrt_price, liquidity, true)?,
+        false => amount_y_delta(sqrt_price, target, liquidity, true)?,
+    };
+    // Short of the target the price moves for all of `less_fee`, rounded in the pool's favour
+    let (next, amount_in) = match less_fee as u128 >= max_in {
+        true => (target, max_in as u64),
+        false => match x_in {
+            true => (
+                sqrt_price_after_x_in(sqrt_price, liquidity, less_fee)?,
+                less_fee,
+            ),
+            false => (
+                sqrt_price_after_y_in(sqrt_price, liquidity, less_fee)?,
+                less_fee,
+            ),
+        },
+    };
+    let amount_out = match x_in {
+        true => amount_y_delta(next, sqrt_price, liquidity, false)?,
+        false => amount_x_delta(sqrt_price, next, liquidity, false)?,
+    };
+    let amount_out = u64::try_from(amount_out).map_err(|_| AmmError::Overflow)?;
+
+    // Stopping short of the target uses up the whole amount, the rest is fee
+    let fee = match next == target {
+        true => {
+            let fee_divisor = 10000 - fee as u128;
+            let fee = (amount_in as u128 * fee as u128 + fee_divisor - 1) / fee_divisor;
+            (fee as u64).min(amount_remaining - amount_in)
+        }
+        false => amount_remaining - amount_in,
+    };
+
+    Ok(SwapStep {
+        sqrt_price: next,
+        amount_in,
+        amount_out,
+        fee,
+    })
+}
+
+// Liquidity plus a signed change
+pub fn add_liquidity_delta(liquidity: u128, delta: i128) -> Result<u128> {
+    match delta < 0 {
+        true => liquidity
+            .checked_sub(delta.unsigned_abs())
+            .ok_or(AmmError::Underflow.into()),
+        false => liquidity
+            .checked_add(delta as u128)
+            .ok_or(AmmError::Overflow.into()),
+    }
+}
@@ -0,0 +1,13 @@
+pub mod math;
+
+pub mod state;
+pub use state::*;
+
+pub mod initialize_cl_pool;
+pub use initialize_cl_pool::*;
+
+pub mod position;
+pub use position::*;
+
+pub mod cl_swap;
+pub use cl_swap::*;
@@ -0,0 +1,441 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{transfer_checked, Mint, TokenAccount, TokenInterface, TransferChecked},
+};
+
+use crate::{
+    assert_non_zero, assert_not_expired,
+    clmm::math::{add_liquidity_delta, amounts_for_liquidity, sqrt_price_at_tick},
+    error::AmmError,
+    helpers::{amount_after_fee, amount_before_fee},
+    ClLiquidityChanged, ClPool, ClTokensCollected, Position, Tick, CL_POOL_SEED, POSITION_SEED,
+    TICK_SEED,
+};
+
+#[derive(Accounts)]
+#[instruction(lower: i32, upper: i32)]
+pub struct OpenPosition<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+    #[account(
+        seeds = [
+            CL_POOL_SEED.as_ref(),
+            pool.mint_x.as_ref(),
+            pool.mint_y.as_ref(),
+            pool.tick_spacing.to_le_bytes().as_ref()
+        ],
+        bump = pool.bump
+    )]
+    pub pool: Box<Account<'info, ClPool>>,
+    #[account(
+        init_if_needed,
+        payer = user,
+        seeds = [TICK_SEED.as_ref(), pool.key().as_ref(), lower.to_le_bytes().as_ref()],
+        bump,
+        space = Tick::LEN
+    )]
+    pub tick_lower: Box<Account<'info, Tick>>,
+    #[account(
+        init_if_needed,
+        payer = user,
+        seeds = [TICK_SEED.as_ref(), pool.key().as_ref(), upper.to_le_bytes().as_ref()],
+        bump,
+        space = Tick::LEN
+    )]
+    pub tick_upper: Box<Account<'info, Tick>>,
+    #[account(
+        init,
+        payer = user,
+        seeds = [
+            POSITION_SEED.as_ref(),
+            pool.key().as_ref(),
+            user.key().as_ref(),
+            lower.to_le_bytes().as_ref(),
+            upper.to_le_bytes().as_ref()
+        ],
+        bump,
+        space = Position::LEN
+    )]
+    pub position: Box<Account<'info, Position>>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> OpenPosition<'info> {
+    pub fn open_position(
+        &mut self,
+        lower: i32,
+        upper: i32,
+        bumps: &OpenPositionBumps,
+    ) -> Result<()> {
+        self.pool.check_range(lower, upper)?;
+
+        // Ticks outlive the positions using them, only set them up the first time
+        for (tick, index, bump) in [
+            (&mut self.tick_lower, lower, bumps.tick_lower),
+            (&mut self.tick_upper, upper, bumps.tick_upper),
+        ] {
+            if tick.pool == Pubkey::default() {
+                tick.pool = self.pool.key();
+                tick.index = index;
+                tick.bump = bump;
+            }
+        }
+
+        self.position.pool = self.pool.key();
+        self.position.owner = self.user.key();
+        self.position.tick_lower = lower;
+        self.position.tick_upper = upper;
+        self.position.bump = bumps.position;
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct ModifyPosition<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+    pub mint_x: Box<InterfaceAccount<'info, Mint>>,
+    pub mint_y: Box<InterfaceAccount<'info, Mint>>,
+    #[account(
+        mut,
+        has_one = mint_x,
+        has_one = mint_y,
+        seeds = [
+            CL_POOL_SEED.as_ref(),
+            mint_x.key().as_ref(),
+            mint_y.key().as_ref(),
+            pool.tick_spacing.to_le_bytes().as_ref()
+        ],
+        bump = pool.bump
+    )]
+    pub pool: Box<Account<'info, ClPool>>,
+    #[account(
+        mut,
+        associated_token::mint = mint_x,
+        associated_token::authority = pool,
+        associated_token::token_program = token_program,
+    )]
+    pub vault_x: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        mut,
+        associated_token::mint = mint_y,
+        associated_token::authority = pool,
+        associated_token::token_program = token_program,
+    )]
+    pub vault_y: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        init_if_needed,
+        payer = user,
+        associated_token::mint = mint_x,
+        associated_token::authority = user,
+        associated_token::token_program = token_program,
+    )]
+    pub user_x: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        init_if_needed,
+        payer = user,
+        associated_token::mint = mint_y,
+        associated_token::authority = user,
+        associated_token::token_program = token_program,
+    )]
+    pub user_y: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        mut,
+        seeds = [
+            TICK_SEED.as_ref(),
+            pool.key().as_ref(),
+            position.tick_lower.to_le_bytes().as_ref()
+        ],
+        bump = tick_lower.bump
+    )]
+    pub tick_lower: Box<Account<'info, Tick>>,
+    #[account(
+        mut,
+        seeds = [
+            TICK_SEED.as_ref(),
+            pool.key().as_ref(),
+            position.tick_upper.to_le_bytes().as_ref()
+        ],
+        bump = tick_upper.bump
+    )]
+    pub tick_upper: Box<Account<'info, Tick>>,
+    #[account(
+        mut,
+        seeds = [
+            POSITION_SEED.as_ref(),
+            pool.key().as_ref(),
+            user.key().as_ref(),
+            position.tick_lower.to_le_bytes().as_ref(),
+            position.tick_upper.to_le_bytes().as_ref()
+        ],
+        bump = position.bump
+    )]
+    pub position: Box<Account<'info, Position>>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> ModifyPosition<'info> {
+    pub fn increase_liquidity(
+        &mut self,
+        liquidity: u128,
+        max_x: u64,
+        max_y: u64,
+        expiration: i64,
+    ) -> Result<()> {
+        assert_not_expired!(expiration);
+        let delta = i128::try_from(liquidity).map_err(|_| AmmError::Overflow)?;
+        require!(delta > 0, AmmError::ZeroBalance);
+
+        let (amount_x, amount_y) = self.modify(delta)?;
+
+        // Enough has to be sent for the amounts to reach the vaults after transfer fees
+        let gross_x = amount_before_fee(&self.mint_x.to_account_info(), amount_x)?;
+        let gross_y = amount_before_fee(&self.mint_y.to_account_info(), amount_y)?;
+        require!(
+            gross_x <= max_x && gross_y <= max_y,
+            AmmError::SlippageExceeded
+        );
+
+        self.deposit_token(true, gross_x)?;
+        self.deposit_token(false, gross_y)?;
+
+        emit!(ClLiquidityChanged {
+            pool: self.pool.key(),
+            position: self.position.key(),
+            owner: self.user.key(),
+            liquidity_delta: delta,
+            amount_x,
+            amount_y,
+        });
+        Ok(())
+    }
+
+    // Pays out the withdrawn tokens along with any fees owed to the position
+    pub fn decrease_liquidity(
+        &mut self,
+        liquidity: u128,
+        min_x: u64,
+        min_y: u64,
+        expiration: i64,
+    ) -> Result<()> {
+        assert_not_expired!(expiration);
+        let delta = i128::try_from(liquidity).map_err(|_| AmmError::Overflow)?;
+        require!(delta > 0, AmmError::ZeroBalance);
+        require!(
+            liquidity <= self.position.liquidity,
+            AmmError::InsufficientBalance
+        );
+
+        let (amount_x, amount_y) = self.modify(-delta)?;
+
+        // Check for slippage on what the user actually receives
+        let received_x = amount_after_fee(&self.mint_x.to_account_info(), amount_x)?;
+        let received_y = amount_after_fee(&self.mint_y.to_account_info(), amount_y)?;
+        require!(
+            received_x >= min_x && received_y >= min_y,
+            AmmError::SlippageExceeded
+        );
+
+        self.position.tokens_owed_x = self
+            .position
+            .tokens_owed_x
+            .checked_add(amount_x)
+            .ok_or(AmmError::Overflow)?;
+        self.position.tokens_owed_y = self
+            .position
+            .tokens_owed_y
+            .checked_add(amount_y)
+            .ok_or(AmmError::Overflow)?;
+
+        emit!(ClLiquidityChanged {
+            pool: self.pool.key(),
+            position: self.position.key(),
+            owner: self.user.key(),
+            liquidity_delta: -delta,
+            amount_x,
+            amount_y,
+        });
+        self.collect()
+    }
+
+    pub fn collect_fees(&mut self) -> Result<()> {
+        // Settle without changing the liquidity
+        self.modify(0)?;
+        assert_non_zero!([self.position.tokens_owed_x | self.position.tokens_owed_y]);
+        self.collect()
+    }
+
+    // Update the ticks, the pool and the position for a liquidity change, and return the
+    // tokens it is worth, rounded up when added and down when removed
+    pub fn modify(&mut self, delta: i128) -> Result<(u64, u64)> {
+        let (lower, upper) = (self.position.tick_lower, self.position.tick_upper);
+
+        let (mut flipped_lower, mut flipped_upper) = (false, false);
+        if delta != 0 {
+            flipped_lower = self.tick_lower.update(&self.pool, delta, false)?;
+            flipped_upper = self.tick_upper.update(&self.pool, delta, true)?;
+        }
+
+        let inside = self
+            .pool
+            .fee_growth_inside(&self.tick_lower, &self.tick_upper);
+        self.position.update(delta, inside)?;
+
+        if flipped_lower {
+            self.pool.flip_tick(lower);
+        }
+        if flipped_upper {
+            self.pool.flip_tick(upper);
+        }
+        // A tick nobody uses anymore starts over the next time it gets liquidity
+        if delta < 0 {
+            if flipped_lower {
+                self.tick_lower.clear();
+            }
+            if flipped_upper {
+                self.tick_upper.clear();
+            }
+        }
+
+        // Only liquidity in range is swapped against
+        if self.pool.tick_current >= lower && self.pool.tick_current < upper {
+            self.pool.liquidity = add_liquidity_delta(self.pool.liquidity, delta)?;
+        }
+
+        amounts_for_liquidity(
+            self.pool.sqrt_price,
+            sqrt_price_at_tick(lower)?,
+            sqrt_price_at_tick(upper)?,
+            delta.unsigned_abs(),
+            delta > 0,
+        )
+    }
+
+    // Pay out everything owed to the position
+    pub fn collect(&mut self) -> Result<()> {
+        let (amount_x, amount_y) = (self.position.tokens_owed_x, self.position.tokens_owed_y);
+        self.position.tokens_owed_x = 0;
+        self.position.tokens_owed_y = 0;
+
+        self.withdraw_token(true, amount_x)?;
+        self.withdraw_token(false, amount_y)?;
+
+        emit!(ClTokensCollected {
+            pool: self.pool.key(),
+            position: self.position.key(),
+            owner: self.user.key(),
+            amount_x,
+            amount_y,
+        });
+        Ok(())
+    }
+
+    pub fn deposit_token(&self, is_x: bool, amount: u64) -> Result<()> {
+        if amount == 0 {
+            return Ok(());
+        }
+        let (from, to, mint, decimals) = match is_x {
+            true => (
+                self.user_x.to_account_info(),
+                self.vault_x.to_account_info(),
+                self.mint_x.to_account_info(),
+                self.mint_x.decimals,
+            ),
+            false => (
+                self.user_y.to_account_info(),
+                self.vault_y.to_account_info(),
+                self.mint_y.to_account_info(),
+                self.mint_y.decimals,
+            ),
+        };
+
+        let cpi_account = TransferChecked {
+            from,
+            to,
+            authority: self.user.to_account_info(),
+            mint,
+        };
+        let cpi_context = CpiContext::new(self.token_program.to_account_info(), cpi_account);
+        transfer_checked(cpi_context, amount, decimals)
+    }
+
+    pub fn withdraw_token(&self, is_x: bool, amount: u64) -> Result<()> {
+        if amount == 0 {
+            return Ok(());
+        }
+        let (from, to, mint, decimals) = match is_x {
+            true => (
+                self.vault_x.to_account_info(),
+                self.user_x.to_account_info(),
+                self.mint_x.to_account_info(),
+                self.mint_x.decimals,
+            ),
+            false => (
+                self.vault_y.to_account_info(),
+                self.user_y.to_account_info(),
+                self.mint_y.to_account_info(),
+                self.mint_y.decimals,
+            ),
+        };
+
+        let cpi_account = TransferChecked {
+            from,
+            to,
+            authority: self.pool.to_account_info(),
+            mint,
+        };
+
+        let spacing = self.pool.tick_spacing.to_le_bytes();
+        let seeds = &[
+            &CL_POOL_SEED.as_ref()[..],
+            self.pool.mint_x.as_ref(),
+            self.pool.mint_y.as_ref(),
+            spacing.as_ref(),
+            &[self.pool.bump],
+        ];
+        let signer_seeds = &[&seeds[..]];
+        let cpi_context = CpiContext::new_with_signer(
+            self.token_program.to_account_info(),
+            cpi_account,
+            signer_seeds,
+        );
+        transfer_checked(cpi_context, amount, decimals)
+    }
+}
+
+#[derive(Accounts)]
+pub struct ClosePosition<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+    pub pool: Box<Account<'info, ClPool>>,
+    #[account(
+        mut,
+        close = user,
+        has_one = pool,
+        seeds = [
+            POSITION_SEED.as_ref(),
+            pool.key().as_ref(),
+            user.key().as_ref(),
+            position.tick_lower.to_le_bytes().as_ref(),
+            position.tick_upper.to_le_bytes().as_ref()
+        ],
+        bump = position.bump
+    )]
+    pub position: Box<Account<'info, Position>>,
+}
+
+impl<'info> ClosePosition<'info> {
+    // Only once everything has been withdrawn and collected, the rent goes back to the owner
+    pub fn close_position(&self) -> Result<()> {
+        require!(
+            self.position.liquidity == 0
+                && self.position.tokens_owed_x == 0
+                && self.position.tokens_owed_y == 0,
+            AmmError::PositionNotEmpty
+        );
+        Ok(())
+    }
+}
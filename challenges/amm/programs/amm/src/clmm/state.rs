@@ -0,0 +1,284 @@
+use crate::{clmm::math::add_liquidity_delta, constants::*, error::AmmError, helpers::mul_div};
+use anchor_lang::prelude::*;
+
+// Ticks are tracked in the bitmap as tick / tick_spacing, one bit each over [-BITMAP_HALF, BITMAP_HALF)
+const BITMAP_HALF: i32 = (TICK_BITMAP_WORDS * 32) as i32;
+
+// Concentrated liquidity pool for a sorted mint pair, at [CL_POOL_SEED, mint_x, mint_y, tick_spacing]
+#[account]
+pub struct ClPool {
+    pub mint_x: Pubkey,
+    pub mint_y: Pubkey,
+    pub tick_spacing: u16,
+    pub fee: u16,
+    pub sqrt_price: u128,          // sqrt(Y per X) as Q64.64
+    pub tick_current: i32, // Greatest tick at or below the price, one below it right after crossing down
+    pub liquidity: u128,   // Liquidity of the positions in range
+    pub fee_growth_global_x: u128, // Fees per unit of liquidity as Q64.64, wraps around
+    pub fee_growth_global_y: u128,
+    pub bitmap: [u64; TICK_BITMAP_WORDS], // Ticks some position starts or ends at
+    pub bump: u8,
+}
+
+impl ClPool {
+    pub const LEN: usize = DISCRIMINATOR_SIZE
+        + (PUBKEY_SIZE * 2)
+        + (U16_SIZE * 2)
+        + U128_SIZE
+        + I32_SIZE
+        + (U128_SIZE * 3)
+        + (U64_SIZE * TICK_BITMAP_WORDS)
+        + U8_SIZE;
+
+    pub fn init(
+        &mut self,
+        mint_x: Pubkey,
+        mint_y: Pubkey,
+        tick_spacing: u16,
+        fee: u16,
+        sqrt_price: u128,
+        tick_current: i32,
+        bump: u8,
+    ) {
+        self.mint_x = mint_x;
+        self.mint_y = mint_y;
+        self.tick_spacing = tick_spacing;
+        self.fee = fee;
+        self.sqrt_price = sqrt_price;
+        self.tick_current = tick_current;
+        self.liquidity = 0;
+        self.fee_growth_global_x = 0;
+        self.fee_growth_global_y = 0;
+        self.bitmap = [0; TICK_BITMAP_WORDS];
+        self.bump = bump;
+    }
+
+    // Lowest and highest tick positions can use, limited by the bitmap and the price range
+    pub fn tick_bounds(&self) -> (i32, i32) {
+        let spacing = self.tick_spacing as i32;
+        (
+            (-BITMAP_HALF * spacing).max(MIN_TICK / spacing * spacing),
+            ((BITMAP_HALF - 1) * spacing).min(MAX_TICK / spacing * spacing),
+        )
+    }
+
+    pub fn check_range(&self, tick_lower: i32, tick_upper: i32) -> Result<()> {
+        let (min, max) = self.tick_bounds();
+        let spacing = self.tick_spacing as i32;
+        require!(
+            min <= tick_lower
+                && tick_lower < tick_upper
+                && tick_upper <= max
+                && tick_lower % spacing == 0
+                && tick_upper % spacing == 0,
+            AmmError::InvalidTick
+        );
+        Ok(())
+    }
+
+    fn bit(&self, tick: i32) -> (usize, u32) {
+        let position = (tick / self.tick_spacing as i32 + BITMAP_HALF) as usize;
+        (position / 64, (position % 64) as u32)
+    }
+
+    // Called when a tick goes from no liquidity to some, or back
+    pub fn flip_tick(&mut self, tick: i32) {
+        let (word, bit) = self.bit(tick);
+        self.bitmap[word] ^= 1 << bit;
+    }
+
+    // Nearest tick with liquidity at or below `tick` (`lte`) or above it, None past the last one
+    pub fn next_initialized_tick(&self, tick: i32, lte: bool) -> Option<i32> {
+        let spacing = self.tick_spacing as i32;
+        let compressed = tick.div_euclid(spacing) + BITMAP_HALF;
+        let limit = BITMAP_HALF * 2;
+
+        let found = match lte {
+            true => {
+                let mut position = compressed.min(limit - 1);
+                while position >= 0 {
+                    let (word, bit) = (position as usize / 64, position % 64);
+                    // Bits at or below `bit`
+                    let bits = self.bitmap[word] & (u64::MAX >> (63 - bit));
+                    if bits != 0 {
+                        break;
+                    }
+                    position -= bit + 1;
+                }
+                match position >= 0 {
+                    true => {
+                        let word = position as usize / 64;
+                        let bits = self.bitmap[word] & (u64::MAX >> (63 - position % 64));
+                        Some(word as i32 * 64 + 63 - bits.leading_zeros() as i32)
+                    }
+                    false => None,
+                }
+            }
+            false => {
+                let mut position = (compressed + 1).max(0);
+                while position < limit {
+                    let (word, bit) = (position as usize / 64, position % 64);
+                    // Bits at or above `bit`
+                    let bits = self.bitmap[word] & (u64::MAX << bit);
+                    if bits != 0 {
+                        break;
+                    }
+                    position += 64 - bit;
+                }
+                match position < limit {
+                    true => {
+                        let word = position as usize / 64;
+                        let bits = self.bitmap[word] & (u64::MAX << (position % 64));
+                        Some(word as i32 * 64 + bits.trailing_zeros() as i32)
+                    }
+                    false => None,
+                }
+            }
+        };
+        found.map(|position| (position - BITMAP_HALF) * spacing)
+    }
+
+    // Credit fees paid in X (or Y) to the liquidity in range
+    pub fn accrue_fee(&mut self, is_x: bool, fee: u64) -> Result<()> {
+        if self.liquidity == 0 {
+            return Ok(());
+        }
+        let growth = mul_div(fee as u128, 1 << 64, self.liquidity).ok_or(AmmError::Overflow)?;
+        match is_x {
+            true => self.fee_growth_global_x = self.fee_growth_global_x.wrapping_add(growth),
+            false => self.fee_growth_global_y = self.fee_growth_global_y.wrapping_add(growth),
+        }
+        Ok(())
+    }
+
+    // Fees per unit of liquidity earned between two ticks, only meaningful as a difference
+    pub fn fee_growth_inside(&self, lower: &Tick, upper: &Tick) -> (u128, u128) {
+        let (global_x, global_y) = (self.fee_growth_global_x, self.fee_growth_global_y);
+        let (below_x, below_y) = match self.tick_current >= lower.index {
+            true => (lower.fee_growth_outside_x, lower.fee_growth_outside_y),
+            false => (
+                global_x.wrapping_sub(lower.fee_growth_outside_x),
+                global_y.wrapping_sub(lower.fee_growth_outside_y),
+            ),
+        };
+        let (above_x, above_y) = match self.tick_current < upper.index {
+            true => (upper.fee_growth_outside_x, upper.fee_growth_outside_y),
+            false => (
+                global_x.wrapping_sub(upper.fee_growth_outside_x),
+                global_y.wrapping_sub(upper.fee_growth_outside_y),
+            ),
+        };
+        (
+            global_x.wrapping_sub(below_x).wrapping_sub(above_x),
+            global_y.wrapping_sub(below_y).wrapping_sub(above_y),
+        )
+    }
+}
+
+// Boundary of one or more positions, at [TICK_SEED, pool, index]
+#[account]
+pub struct Tick {
+    pub pool: Pubkey,
+    pub index: i32,
+    pub liquidity_gross: u128, // Liquidity of every position using this tick
+    pub liquidity_net: i128,   // Liquidity added to the pool when the price crosses it upwards
+    pub fee_growth_outside_x: u128, // Fee growth on the other side of the tick from the price
+    pub fee_growth_outside_y: u128,
+    pub bump: u8,
+}
+
+impl Tick {
+    pub const LEN: usize = DISCRIMINATOR_SIZE
+        + PUBKEY_SIZE
+        + I32_SIZE
+        + U128_SIZE
+        + I128_SIZE
+        + (U128_SIZE * 2)
+        + U8_SIZE;
+
+    // Returns whether the tick went from no liquidity to some, or back
+    pub fn update(&mut self, pool: &ClPool, liquidity_delta: i128, upper: bool) -> Result<bool> {
+        let gross_before = self.liquidity_gross;
+        let gross_after = add_liquidity_delta(gross_before, liquidity_delta)?;
+
+        // Fees so far are assumed to have been earned below the tick
+        if gross_before == 0 && self.index <= pool.tick_current {
+            self.fee_growth_outside_x = pool.fee_growth_global_x;
+            self.fee_growth_outside_y = pool.fee_growth_global_y;
+        }
+
+        self.liquidity_gross = gross_after;
+        self.liquidity_net = match upper {
+            true => self.liquidity_net.checked_sub(liquidity_delta),
+            false => self.liquidity_net.checked_add(liquidity_delta),
+        }
+        .ok_or(AmmError::Overflow)?;
+        Ok((gross_before == 0) != (gross_after == 0))
+    }
+
+    pub fn clear(&mut self) {
+        self.liquidity_net = 0;
+        self.fee_growth_outside_x = 0;
+        self.fee_growth_outside_y = 0;
+    }
+
+    // The price moves to the other side, returns the liquidity to add when crossing upwards
+    pub fn cross(&mut self, fee_growth_global_x: u128, fee_growth_global_y: u128) -> i128 {
+        self.fee_growth_outside_x = fee_growth_global_x.wrapping_sub(self.fee_growth_outside_x);
+        self.fee_growth_outside_y = fee_growth_global_y.wrapping_sub(self.fee_growth_outside_y);
+        self.liquidity_net
+    }
+}
+
+// Liquidity an owner provides over [tick_lower, tick_upper), at [POSITION_SEED, pool, owner, tick_lower, tick_upper]
+#[account]
+pub struct Position {
+    pub pool: Pubkey,
+    pub owner: Pubkey,
+    pub tick_lower: i32,
+    pub tick_upper: i32,
+    pub liquidity: u128,
+    pub fee_growth_inside_x_last: u128, // Pool's fee growth inside the range when fees were last settled
+    pub fee_growth_inside_y_last: u128,
+    pub tokens_owed_x: u64, // Settled fees and withdrawn liquidity that have not been collected yet
+    pub tokens_owed_y: u64,
+    pub bump: u8,
+}
+
+impl Position {
+    pub const LEN: usize = DISCRIMINATOR_SIZE
+        + (PUBKEY_SIZE * 2)
+        + (I32_SIZE * 2)
+        + (U128_SIZE * 3)
+        + (U64_SIZE * 2)
+        + U8_SIZE;
+
+    // Credit fees earned since the last settlement, then apply the liquidity change
+    pub fn update(
+        &mut self,
+        liquidity_delta: i128,
+        (inside_x, inside_y): (u128, u128),
+    ) -> Result<()> {
+        let earned_x = mul_div(
+            self.liquidity,
+            inside_x.wrapping_sub(self.fee_growth_inside_x_last),
+            1 << 64,
+        )
+        .ok_or(AmmError::Overflow)?;
+        let earned_y = mul_div(
+            self.liquidity,
+            inside_y.wrapping_sub(self.fee_growth_inside_y_last),
+            1 << 64,
+        )
+        .ok_or(AmmError::Overflow)?;
+
+        self.tokens_owed_x =
+            u64::try_from(self.tokens_owed_x as u128 + earned_x).map_err(|_| AmmError::Overflow)?;
+        self.tokens_owed_y =
+            u64::try_from(self.tokens_owed_y as u128 + earned_y).map_err(|_| AmmError::Overflow)?;
+        self.fee_growth_inside_x_last = inside_x;
+        self.fee_growth_inside_y_last = inside_y;
+        self.liquidity = add_liquidity_delta(self.liquidity, liquidity_delta)?;
+        Ok(())
+    }
+}
@@ -7,6 +7,56 @@ pub const CONFIG_SEED: &'static [u8] = b"config";
 #[constant]
 pub const AUTH_SEED: &'static [u8] = b"auth";
 #[constant]
+pub const MINIMUM_LIQUIDITY: u64 = 1000;
+#[constant]
+pub const ORACLE_SEED: &'static [u8] = b"oracle";
+#[constant]
+pub const OBSERVATIONS: usize = 32;
+#[constant]
+pub const STATS_SEED: &'static [u8] = b"stats";
+#[constant]
+pub const OBSERVATION_INTERVAL: i64 = 300;
+#[constant]
+pub const MIN_AMP: u64 = 1;
+#[constant]
+pub const MAX_AMP: u64 = 1_000_000;
+#[constant]
+pub const MAX_AMP_CHANGE: u64 = 10;
+#[constant]
+pub const MIN_RAMP_DURATION: i64 = 86400;
+#[constant]
+pub const FLASH_LOAN_FEE: u16 = 9;
+#[constant]
+pub const VOLATILITY_DECAY: i64 = 600;
+#[constant]
+pub const VOLATILITY_FEE_DIVISOR: u64 = 10;
+#[constant]
+pub const FARM_SEED: &'static [u8] = b"farm";
+#[constant]
+pub const STAKE_SEED: &'static [u8] = b"stake";
+#[constant]
+pub const PAIR_SEED: &'static [u8] = b"pair";
+#[constant]
+pub const ALLOWLIST_SEED: &'static [u8] = b"allowlist";
+#[constant]
+pub const MAX_LP_DECIMALS: u8 = 9;
+#[constant]
+pub const METADATA_SEED: &'static [u8] = b"metadata";
+#[constant]
+pub const CL_POOL_SEED: &'static [u8] = b"cl_pool";
+#[constant]
+pub const TICK_SEED: &'static [u8] = b"tick";
+#[constant]
+pub const POSITION_SEED: &'static [u8] = b"position";
+#[constant]
+pub const MIN_TICK: i32 = -443636;
+#[constant]
+pub const MAX_TICK: i32 = 443636;
+#[constant]
+pub const MAX_TICK_SPACING: u16 = 16384;
+#[constant]
+pub const TICK_BITMAP_WORDS: usize = 128;
+#[constant]
 pub const DISCRIMINATOR_SIZE: usize = std::mem::size_of::<u64>();
 #[constant]
 pub const PUBKEY_SIZE: usize = std::mem::size_of::<Pubkey>();
@@ -15,6 +65,14 @@ pub const U8_SIZE: usize = std::mem::size_of::<u8>();
 #[constant]
 pub const U64_SIZE: usize = std::mem::size_of::<u64>();
 #[constant]
+pub const U128_SIZE: usize = std::mem::size_of::<u128>();
+#[constant]
+pub const I32_SIZE: usize = std::mem::size_of::<i32>();
+#[constant]
+pub const I128_SIZE: usize = std::mem::size_of::<i128>();
+#[constant]
+pub const I64_SIZE: usize = std::mem::size_of::<i64>();
+#[constant]
 pub const U16_SIZE: usize = std::mem::size_of::<u16>();
 #[constant]
 pub const BOOL_SIZE: usize = std::mem::size_of::<bool>();
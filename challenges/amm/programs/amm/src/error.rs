@@ -39,6 +39,52 @@ pub enum AmmError {
     InsufficientBalance,
     #[msg("Zero balance.")]
     ZeroBalance,
+    #[msg("Not enough price history for the requested window.")]
+    OracleWindowTooLong,
+    #[msg("Invalid swap route.")]
+    InvalidRoute,
+    #[msg("Non-transferable mints are not supported.")]
+    NonTransferableMint,
+    #[msg("Mints with a transfer hook are not supported.")]
+    TransferHookNotSupported,
+    #[msg("Unsupported mint extension.")]
+    UnsupportedMintExtension,
+    #[msg("Invalid amplification coefficient.")]
+    InvalidAmplification,
+    #[msg("Invalid amplification ramp.")]
+    InvalidRamp,
+    #[msg("Not supported by this curve.")]
+    UnsupportedCurve,
+    #[msg("Flash loan is not repaid in this transaction.")]
+    FlashLoanNotRepaid,
+    #[msg("Flash loans can't be taken through CPI.")]
+    FlashLoanCpi,
+    #[msg("No flash loan to repay.")]
+    NoFlashLoan,
+    #[msg("Pool still holds liquidity.")]
+    PoolNotEmpty,
+    #[msg("Pool mints must be different.")]
+    IdenticalMints,
+    #[msg("Pool mints must be sorted, mint_x < mint_y.")]
+    UnsortedMints,
+    #[msg("Invalid tick.")]
+    InvalidTick,
+    #[msg("Invalid tick spacing.")]
+    InvalidTickSpacing,
+    #[msg("Invalid sqrt price.")]
+    InvalidSqrtPrice,
+    #[msg("Missing or unexpected tick account.")]
+    InvalidTickAccount,
+    #[msg("Position still holds liquidity or fees.")]
+    PositionNotEmpty,
+    #[msg("Pool is not registered for this pair.")]
+    InvalidPair,
+    #[msg("Registered pool still holds liquidity.")]
+    PairInUse,
+    #[msg("Wallet is not on the pool's allowlist.")]
+    NotAllowlisted,
+    #[msg("Pool has no allowlist.")]
+    PoolNotPermissioned,
 }
 
 impl From<CurveError> for AmmError {
@@ -0,0 +1,217 @@
+use anchor_lang::prelude::*;
+
+use crate::CurveType;
+
+#[event]
+pub struct PoolLocked {
+    pub config: Pubkey,
+    pub authority: Pubkey,
+}
+
+#[event]
+pub struct PoolUnlocked {
+    pub config: Pubkey,
+    pub authority: Pubkey,
+}
+
+#[event]
+pub struct FeeUpdated {
+    pub config: Pubkey,
+    pub old_fee: u16,
+    pub new_fee: u16,
+}
+
+#[event]
+pub struct AuthorityTransferred {
+    pub config: Pubkey,
+    pub old_authority: Pubkey,
+    pub new_authority: Pubkey,
+}
+
+#[event]
+pub struct AuthorityRenounced {
+    pub config: Pubkey,
+    pub old_authority: Pubkey,
+}
+
+#[event]
+pub struct ProtocolFeeUpdated {
+    pub config: Pubkey,
+    pub old_protocol_fee: u16,
+    pub new_protocol_fee: u16,
+}
+
+#[event]
+pub struct DynamicFeeUpdated {
+    pub config: Pubkey,
+    pub enabled: bool,
+    pub min_fee: u16,
+    pub max_fee: u16,
+}
+
+#[event]
+pub struct ProtocolFeesCollected {
+    pub config: Pubkey,
+    pub treasury_x: Pubkey,
+    pub treasury_y: Pubkey,
+    pub amount_x: u64,
+    pub amount_y: u64,
+}
+
+#[event]
+pub struct PoolInitialized {
+    pub config: Pubkey,
+    pub initializer: Pubkey,
+    pub authority: Option<Pubkey>,
+    pub mint_x: Pubkey,
+    pub mint_y: Pubkey,
+    pub mint_lp: Pubkey,
+    pub seed: u64,
+    pub fee: u16,
+    pub curve: CurveType,
+    pub amp: u64,
+    pub lp_decimals: u8,
+    pub permissioned: bool,
+}
+
+#[event]
+pub struct LiquidityAdded {
+    pub config: Pubkey,
+    pub user: Pubkey,
+    pub amount_x: u64,
+    pub amount_y: u64,
+    pub lp_amount: u64,
+}
+
+#[event]
+pub struct Swapped {
+    pub config: Pubkey,
+    pub user: Pubkey,
+    pub is_x: bool,
+    pub amount_in: u64,
+    pub amount_out: u64,
+    pub fee: u64,
+    pub reserve_x_before: u64,
+    pub reserve_y_before: u64,
+    pub reserve_x_after: u64,
+    pub reserve_y_after: u64,
+}
+
+#[event]
+pub struct AmpRamped {
+    pub config: Pubkey,
+    pub initial_amp: u64,
+    pub target_amp: u64,
+    pub ramp_start: i64,
+    pub ramp_end: i64,
+}
+
+#[event]
+pub struct FlashLoanRepaid {
+    pub config: Pubkey,
+    pub user: Pubkey,
+    pub is_x: bool,
+    pub amount: u64,
+    pub fee: u64,
+}
+
+#[event]
+pub struct PoolClosed {
+    pub config: Pubkey,
+    pub authority: Pubkey,
+    pub recipient: Pubkey,
+}
+
+#[event]
+pub struct FarmFunded {
+    pub config: Pubkey,
+    pub farm: Pubkey,
+    pub amount: u64,
+    pub reward_rate: u64,
+}
+
+#[event]
+pub struct LpStaked {
+    pub config: Pubkey,
+    pub user: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct LpUnstaked {
+    pub config: Pubkey,
+    pub user: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct RewardsClaimed {
+    pub config: Pubkey,
+    pub user: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct PairRegistered {
+    pub pair: Pubkey,
+    pub config: Pubkey,
+    pub mint_x: Pubkey,
+    pub mint_y: Pubkey,
+}
+
+#[event]
+pub struct AllowlistUpdated {
+    pub config: Pubkey,
+    pub wallet: Pubkey,
+    pub allowed: bool,
+}
+
+#[event]
+pub struct PairContested {
+    pub pair: Pubkey,
+    pub old_config: Pubkey,
+    pub new_config: Pubkey,
+}
+
+#[event]
+pub struct ClPoolInitialized {
+    pub pool: Pubkey,
+    pub initializer: Pubkey,
+    pub mint_x: Pubkey,
+    pub mint_y: Pubkey,
+    pub tick_spacing: u16,
+    pub fee: u16,
+    pub sqrt_price: u128,
+    pub tick: i32,
+}
+
+#[event]
+pub struct ClLiquidityChanged {
+    pub pool: Pubkey,
+    pub position: Pubkey,
+    pub owner: Pubkey,
+    pub liquidity_delta: i128,
+    pub amount_x: u64,
+    pub amount_y: u64,
+}
+
+#[event]
+pub struct ClTokensCollected {
+    pub pool: Pubkey,
+    pub position: Pubkey,
+    pub owner: Pubkey,
+    pub amount_x: u64,
+    pub amount_y: u64,
+}
+
+#[event]
+pub struct ClSwapped {
+    pub pool: Pubkey,
+    pub user: Pubkey,
+    pub is_x: bool,
+    pub amount_in: u64,
+    pub amount_out: u64,
+    pub fee: u64,
+    pub sqrt_price: u128,
+    pub tick: i32,
+}
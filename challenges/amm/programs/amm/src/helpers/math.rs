@@ -0,0 +1,140 @@
+use anchor_lang::prelude::*;
+
+use crate::error::AmmError;
+
+// Smallest input that makes the constant product curve pay out `amount_out`
+// once the fee in basis points has been taken from the input
+pub fn amount_in_for_exact_out(
+    reserve_in: u64,
+    reserve_out: u64,
+    amount_out: u64,
+    fee: u16,
+) -> Result<u64> {
+    require!(fee < 10000, AmmError::InvalidFee);
+    require!(amount_out < reserve_out, AmmError::InsufficientBalance);
+
+    let k = (reserve_in as u128)
+        .checked_mul(reserve_out as u128)
+        .ok_or(AmmError::Overflow)?;
+    let remaining_out = (reserve_out - amount_out) as u128;
+
+    // Net input (after fee) needed to keep k constant, rounded up
+    let net_in = k.checked_add(remaining_out - 1).ok_or(AmmError::Overflow)? / remaining_out
+        - reserve_in as u128;
+
+    // Gross up by the fee, rounded up
+    let fee_divisor = 10000 - fee as u128;
+    let amount_in = net_in
+        .checked_mul(10000)
+        .ok_or(AmmError::Overflow)?
+        .checked_add(fee_divisor - 1)
+        .ok_or(AmmError::Overflow)?
+        / fee_divisor;
+
+    u64::try_from(amount_in).map_err(|_| AmmError::Overflow.into())
+}
+
+// Portion of `amount_in` to swap so that what is left over and the swap output
+// match the pool ratio after the swap, letting the whole amount be deposited
+pub fn single_sided_swap_amount(
+    reserve_in: u64,
+    reserve_out: u64,
+    amount_in: u64,
+    fee: u16,
+) -> Result<u64> {
+    require!(fee < 10000, AmmError::InvalidFee);
+    require!(
+        reserve_in > 0 && reserve_out > 0,
+        AmmError::NoLiquidityInPool
+    );
+
+    let (mut low, mut high) = (0u64, amount_in);
+    while low < high {
+        let mid = low + (high - low + 1) / 2;
+        let net = mid as u128 - mid as u128 * fee as u128 / 10000;
+        let x = reserve_in as u128 + mid as u128;
+        let out = reserve_out as u128 * net / (reserve_in as u128 + net);
+        let y = reserve_out as u128 - out;
+
+        // Swap more while the leftover input still outweighs the output
+        let leftover = (amount_in - mid) as u128 * y;
+        match leftover >= out.checked_mul(x).ok_or(AmmError::Overflow)? {
+            true => low = mid,
+            false => high = mid - 1,
+        }
+    }
+
+    Ok(low)
+}
+
+// Integer square root, rounded down
+pub fn sqrt_u128(n: u128) -> u128 {
+    if n < 2 {
+        return n;
+    }
+
+    let mut x = n;
+    let mut y = (x + 1) / 2;
+    while y < x {
+        x = y;
+        y = (x + n / x) / 2;
+    }
+    x
+}
+
+// a * b / c rounded down, with a 256-bit intermediate product
+pub fn mul_div(a: u128, b: u128, c: u128) -> Option<u128> {
+    if c == 0 {
+        return None;
+    }
+    if let Some(product) = a.checked_mul(b) {
+        return Some(product / c);
+    }
+
+    // The quotient doesn't fit in 128 bits
+    let (hi, lo) = widening_mul(a, b);
+    if hi >= c {
+        return None;
+    }
+
+    // Long division of the 256-bit product, one bit at a time
+    let (mut remainder, mut quotient) = (hi, 0u128);
+    for i in (0..128).rev() {
+        let carry = remainder >> 127;
+        remainder = (remainder << 1) | ((lo >> i) & 1);
+        quotient <<= 1;
+        if carry == 1 || remainder >= c {
+            remainder = remainder.wrapping_sub(c);
+            quotient |= 1;
+        }
+    }
+    Some(quotient)
+}
+
+// a * b / c rounded up, with a 256-bit intermediate product
+pub fn mul_div_ceil(a: u128, b: u128, c: u128) -> Option<u128> {
+    let quotient = mul_div(a, b, c)?;
+    let (hi, lo) = widening_mul(a, b);
+    let (q_hi, q_lo) = widening_mul(quotient, c);
+    match (q_hi, q_lo) == (hi, lo) {
+        true => Some(quotient),
+        false => quotient.checked_add(1),
+    }
+}
+
+// Full 256-bit product of two u128 as (high, low)
+pub fn widening_mul(a: u128, b: u128) -> (u128, u128) {
+    let mask = u64::MAX as u128;
+    let (a_hi, a_lo) = (a >> 64, a & mask);
+    let (b_hi, b_lo) = (b >> 64, b & mask);
+
+    let ll = a_lo * b_lo;
+    let lh = a_lo * b_hi;
+    let hl = a_hi * b_lo;
+    let hh = a_hi * b_hi;
+
+    let mid = (ll >> 64) + (lh & mask) + (hl & mask);
+    let lo = (ll & mask) | (mid << 64);
+    let hi = hh + (lh >> 64) + (hl >> 64) + (mid >> 64);
+    (hi, lo)
+}
@@ -1,3 +1,12 @@
+pub mod math;
+pub use math::*;
+
+pub mod stable;
+pub use stable::*;
+
+pub mod token;
+pub use token::*;
+
 #[macro_export]
 macro_rules! assert_non_zero {
     ($array:expr) => {
@@ -25,6 +34,15 @@ macro_rules! assert_not_expired {
     };
 }
 
+#[macro_export]
+macro_rules! assert_allowlisted {
+    ($x:expr) => {
+        if $x.config.permissioned && $x.allowlist_entry.is_none() {
+            return err!(AmmError::NotAllowlisted)
+        }
+    };
+}
+
 #[macro_export]
 macro_rules! has_update_authority {
     ($x:expr) => {
@@ -0,0 +1,141 @@
+use anchor_lang::prelude::*;
+
+use crate::{error::AmmError, helpers::mul_div};
+
+// Number of tokens in a pool
+const N_COINS: u128 = 2;
+
+// Newton's method gives up after this many rounds
+const MAX_ITERATIONS: usize = 255;
+
+// StableSwap invariant D for two reserves and an amplification coefficient:
+// A * n^n * (x + y) + D = A * n^n * D + D^(n + 1) / (n^n * x * y)
+pub fn stable_invariant(x: u64, y: u64, amp: u64) -> Result<u128> {
+    let (x, y) = (x as u128, y as u128);
+    let sum = x + y;
+    if sum == 0 {
+        return Ok(0);
+    }
+    require!(x > 0 && y > 0, AmmError::NoLiquidityInPool);
+    require!(amp > 0, AmmError::InvalidAmplification);
+
+    let ann = amp as u128 * N_COINS * N_COINS;
+    let mut d = sum;
+    for _ in 0..MAX_ITERATIONS {
+        // D^(n + 1) / (n^n * x * y)
+        let d_p = cube_div(d, x * y)? / (N_COINS * N_COINS);
+
+        let numerator = ann
+            .checked_mul(sum)
+            .and_then(|n| n.checked_add(d_p.checked_mul(N_COINS)?))
+            .ok_or(AmmError::Overflow)?;
+        let denominator = (ann - 1)
+            .checked_mul(d)
+            .and_then(|n| n.checked_add(d_p.checked_mul(N_COINS + 1)?))
+            .ok_or(AmmError::Overflow)?;
+
+        let previous = d;
+        d = mul_div(numerator, d, denominator).ok_or(AmmError::Overflow)?;
+        if d.abs_diff(previous) <= 1 {
+            return Ok(d);
+        }
+    }
+
+    err!(AmmError::CurveError)
+}
+
+// Reserve of the other token that keeps the invariant at `d` once one reserve is `reserve`
+pub fn stable_reserve(reserve: u64, d: u128, amp: u64) -> Result<u128> {
+    require!(reserve > 0, AmmError::NoLiquidityInPool);
+    require!(amp > 0, AmmError::InvalidAmplification);
+
+    let reserve = reserve as u128;
+    let ann = amp as u128 * N_COINS * N_COINS;
+
+    // c = D^(n + 1) / (n^n * reserve * Ann), b = reserve + D / Ann
+    let c = cube_div(d, reserve * ann)? / (N_COINS * N_COINS);
+    let b = reserve + d / ann;
+
+    let mut y = d;
+    for _ in 0..MAX_ITERATIONS {
+        // y = (y^2 + c) / (2y + b - D)
+        let denominator = (y * 2 + b).checked_sub(d).ok_or(AmmError::Underflow)?;
+        let previous = y;
+        y = mul_div(y, y, denominator)
+            .and_then(|n| n.checked_add(c / denominator))
+            .ok_or(AmmError::Overflow)?;
+        if y.abs_diff(previous) <= 1 {
+            // Round up, the flooring above can leave y a unit short
+            return Ok(y + 1);
+        }
+    }
+
+    err!(AmmError::CurveError)
+}
+
+// Output and fee for swapping `amount_in` on the StableSwap curve, the fee in basis
+// points is taken from the input and the output is rounded down in favour of the pool
+pub fn stable_swap_out(
+    reserve_in: u64,
+    reserve_out: u64,
+    amount_in: u64,
+    amp: u64,
+    fee: u16,
+) -> Result<(u64, u64)> {
+    require!(fee < 10000, AmmError::InvalidFee);
+
+    let fee_amount = (amount_in as u128 * fee as u128 / 10000) as u64;
+    let net_in = amount_in - fee_amount;
+
+    let d = stable_invariant(reserve_in, reserve_out, amp)?;
+    let new_in = reserve_in.checked_add(net_in).ok_or(AmmError::Overflow)?;
+    let new_out = stable_reserve(new_in, d, amp)?;
+
+    let amount_out = (reserve_out as u128).saturating_sub(new_out + 1);
+    Ok((amount_out as u64, fee_amount))
+}
+
+// Smallest input that makes the StableSwap curve pay out `amount_out`
+// once the fee in basis points has been taken from the input
+pub fn stable_amount_in_for_exact_out(
+    reserve_in: u64,
+    reserve_out: u64,
+    amount_out: u64,
+    amp: u64,
+    fee: u16,
+) -> Result<u64> {
+    require!(fee < 10000, AmmError::InvalidFee);
+
+    // Leave room for the unit `stable_swap_out` rounds away from the output
+    let new_out = amount_out
+        .checked_add(1)
+        .and_then(|n| reserve_out.checked_sub(n))
+        .filter(|n| *n > 0)
+        .ok_or(AmmError::InsufficientBalance)?;
+
+    let d = stable_invariant(reserve_in, reserve_out, amp)?;
+    let new_in = stable_reserve(new_out, d, amp)?;
+
+    // Net input needed to hold the invariant, plus one to cover rounding
+    let net_in = new_in.saturating_sub(reserve_in as u128) + 1;
+
+    // Gross up by the fee, rounded up
+    let fee_divisor = 10000 - fee as u128;
+    let amount_in = net_in
+        .checked_mul(10000)
+        .ok_or(AmmError::Overflow)?
+        .checked_add(fee_divisor - 1)
+        .ok_or(AmmError::Overflow)?
+        / fee_divisor;
+
+    u64::try_from(amount_in).map_err(|_| AmmError::Overflow.into())
+}
+
+// d^3 / divisor, keeping d^2 whole when it fits so small reserves don't lose precision
+fn cube_div(d: u128, divisor: u128) -> Result<u128> {
+    let result = match d.checked_mul(d) {
+        Some(d_squared) => mul_div(d_squared, d, divisor),
+        None => mul_div(d, d, divisor).and_then(|n| n.checked_mul(d)),
+    };
+    result.ok_or(AmmError::Overflow.into())
+}
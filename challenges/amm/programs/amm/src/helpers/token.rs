@@ -0,0 +1,65 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_2022::{
+    self,
+    spl_token_2022::{
+        extension::{
+            transfer_fee::TransferFeeConfig, BaseStateWithExtensions, ExtensionType,
+            StateWithExtensions,
+        },
+        state::Mint,
+    },
+};
+
+use crate::error::AmmError;
+
+// Reject Token-2022 mints whose extensions would break the vault accounting
+pub fn assert_supported_mint(mint: &AccountInfo) -> Result<()> {
+    if *mint.owner != token_2022::ID {
+        return Ok(());
+    }
+
+    let data = mint.try_borrow_data()?;
+    let state = StateWithExtensions::<Mint>::unpack(&data)?;
+    for extension in state.get_extension_types()? {
+        match extension {
+            ExtensionType::NonTransferable => return err!(AmmError::NonTransferableMint),
+            ExtensionType::TransferHook => return err!(AmmError::TransferHookNotSupported),
+            ExtensionType::PermanentDelegate | ExtensionType::ConfidentialTransferMint => {
+                return err!(AmmError::UnsupportedMintExtension)
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+fn with_transfer_fee_config<T>(
+    mint: &AccountInfo,
+    default: T,
+    f: impl FnOnce(&TransferFeeConfig, u64) -> Option<T>,
+) -> Result<T> {
+    if *mint.owner != token_2022::ID {
+        return Ok(default);
+    }
+
+    let data = mint.try_borrow_data()?;
+    let state = StateWithExtensions::<Mint>::unpack(&data)?;
+    match state.get_extension::<TransferFeeConfig>() {
+        Ok(config) => Ok(f(config, Clock::get()?.epoch).ok_or(AmmError::Overflow)?),
+        Err(_) => Ok(default),
+    }
+}
+
+// Amount that arrives at the destination once the mint has withheld its transfer fee
+pub fn amount_after_fee(mint: &AccountInfo, amount: u64) -> Result<u64> {
+    with_transfer_fee_config(mint, amount, |config, epoch| {
+        amount.checked_sub(config.calculate_epoch_fee(epoch, amount)?)
+    })
+}
+
+// Amount that has to be sent for `amount` to arrive at the destination
+pub fn amount_before_fee(mint: &AccountInfo, amount: u64) -> Result<u64> {
+    with_transfer_fee_config(mint, amount, |config, epoch| {
+        amount.checked_add(config.calculate_inverse_epoch_fee(epoch, amount)?)
+    })
+}
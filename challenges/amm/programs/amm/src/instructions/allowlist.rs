@@ -0,0 +1,72 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    error::AmmError, has_update_authority, AllowlistEntry, AllowlistUpdated, Config,
+    ALLOWLIST_SEED, CONFIG_SEED,
+};
+
+#[derive(Accounts)]
+#[instruction(wallet: Pubkey)]
+pub struct AddToAllowlist<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+    #[account(
+        seeds = [CONFIG_SEED.as_ref(), config.seed.to_le_bytes().as_ref()],
+        bump = config.config_bump
+    )]
+    pub config: Box<Account<'info, Config>>,
+    #[account(
+        init,
+        payer = user,
+        seeds = [ALLOWLIST_SEED.as_ref(), config.key().as_ref(), wallet.as_ref()],
+        bump,
+        space = AllowlistEntry::LEN
+    )]
+    pub allowlist_entry: Box<Account<'info, AllowlistEntry>>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> AddToAllowlist<'info> {
+    pub fn add_to_allowlist(&mut self, wallet: Pubkey, bump: u8) -> Result<()> {
+        has_update_authority!(self);
+        require!(self.config.permissioned, AmmError::PoolNotPermissioned);
+        self.allowlist_entry.init(self.config.key(), wallet, bump);
+        emit!(AllowlistUpdated {
+            config: self.config.key(),
+            wallet,
+            allowed: true,
+        });
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct RemoveFromAllowlist<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+    #[account(
+        seeds = [CONFIG_SEED.as_ref(), config.seed.to_le_bytes().as_ref()],
+        bump = config.config_bump
+    )]
+    pub config: Box<Account<'info, Config>>,
+    #[account(
+        mut,
+        close = user,
+        has_one = config,
+        seeds = [ALLOWLIST_SEED.as_ref(), config.key().as_ref(), allowlist_entry.wallet.as_ref()],
+        bump = allowlist_entry.bump
+    )]
+    pub allowlist_entry: Box<Account<'info, AllowlistEntry>>,
+}
+
+impl<'info> RemoveFromAllowlist<'info> {
+    pub fn remove_from_allowlist(&mut self) -> Result<()> {
+        has_update_authority!(self);
+        emit!(AllowlistUpdated {
+            config: self.config.key(),
+            wallet: self.allowlist_entry.wallet,
+            allowed: false,
+        });
+        Ok(())
+    }
+}
@@ -0,0 +1,236 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{
+    burn, close_account, transfer_checked, Burn, CloseAccount, Mint, TokenAccount, TokenInterface,
+    TransferChecked,
+};
+
+use crate::{
+    error::AmmError, has_update_authority, Config, Oracle, PoolClosed, Stats, AUTH_SEED,
+    CONFIG_SEED, LP_SEED, ORACLE_SEED, STATS_SEED,
+};
+
+#[derive(Accounts)]
+pub struct ClosePool<'info> {
+    pub user: Signer<'info>,
+    /// CHECK: Only receives the reclaimed rent
+    #[account(mut)]
+    pub recipient: UncheckedAccount<'info>,
+    pub mint_x: Box<InterfaceAccount<'info, Mint>>,
+    pub mint_y: Box<InterfaceAccount<'info, Mint>>,
+    #[account(
+        mut,
+        seeds = [LP_SEED.as_ref(), config.key().as_ref()],
+        bump = config.lp_bump
+    )]
+    pub mint_lp: Box<InterfaceAccount<'info, Mint>>,
+    #[account(
+        mut,
+        associated_token::mint = mint_x,
+        associated_token::authority = auth,
+        associated_token::token_program = token_program,
+    )]
+    pub vault_x: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        mut,
+        associated_token::mint = mint_y,
+        associated_token::authority = auth,
+        associated_token::token_program = token_program,
+    )]
+    pub vault_y: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        mut,
+        associated_token::mint = mint_lp,
+        associated_token::authority = auth,
+        associated_token::token_program = token_program,
+    )]
+    pub vault_lp: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        mut,
+        associated_token::mint = mint_x,
+        associated_token::authority = user,
+        associated_token::token_program = token_program,
+    )]
+    pub treasury_x: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        mut,
+        associated_token::mint = mint_y,
+        associated_token::authority = user,
+        associated_token::token_program = token_program,
+    )]
+    pub treasury_y: Box<InterfaceAccount<'info, TokenAccount>>,
+    /// CHECK: This is safe because it's just used to sign
+    #[account(
+        seeds = [AUTH_SEED.as_ref(), config.key().as_ref()],
+        bump = config.auth_bump
+    )]
+    pub auth: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        close = recipient,
+        has_one = mint_x,
+        has_one = mint_y,
+        seeds = [CONFIG_SEED.as_ref(), config.seed.to_le_bytes().as_ref()],
+        bump = config.config_bump
+    )]
+    pub config: Box<Account<'info, Config>>,
+    #[account(
+        mut,
+        close = recipient,
+        seeds = [ORACLE_SEED.as_ref(), config.key().as_ref()],
+        bump = oracle.bump
+    )]
+    pub oracle: Box<Account<'info, Oracle>>,
+    #[account(
+        mut,
+        close = recipient,
+        seeds = [STATS_SEED.as_ref(), config.key().as_ref()],
+        bump = stats.bump
+    )]
+    pub stats: Box<Account<'info, Stats>>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+impl<'info> ClosePool<'info> {
+    pub fn close_pool(&mut self) -> Result<()> {
+        has_update_authority!(self);
+        require!(self.config.flash_loan == 0, AmmError::FlashLoanNotRepaid);
+        // Protocol fees have to be collected before the vaults go away
+        require!(
+            self.config.protocol_fees_x == 0 && self.config.protocol_fees_y == 0,
+            AmmError::PoolNotEmpty
+        );
+        // The only LP left may be the minimum liquidity locked in `vault_lp` on the first deposit
+        require!(
+            self.mint_lp.supply == self.vault_lp.amount,
+            AmmError::PoolNotEmpty
+        );
+
+        // The locked liquidity is only ever held by the pool, so it is burned
+        self.burn_vault(
+            self.vault_lp.to_account_info(),
+            self.mint_lp.to_account_info(),
+            self.vault_lp.amount,
+        )?;
+        // What it backed, plus any donations, goes to the authority
+        self.transfer_vault(
+            self.vault_x.to_account_info(),
+            self.treasury_x.to_account_info(),
+            self.mint_x.to_account_info(),
+            self.mint_x.decimals,
+            self.vault_x.amount,
+        )?;
+        self.transfer_vault(
+            self.vault_y.to_account_info(),
+            self.treasury_y.to_account_info(),
+            self.mint_y.to_account_info(),
+            self.mint_y.decimals,
+            self.vault_y.amount,
+        )?;
+
+        self.mint_lp.reload()?;
+        self.vault_x.reload()?;
+        self.vault_y.reload()?;
+        require!(
+            self.mint_lp.supply == 0 && self.vault_x.amount == 0 && self.vault_y.amount == 0,
+            AmmError::PoolNotEmpty
+        );
+
+        self.close_vault(self.vault_x.to_account_info())?;
+        self.close_vault(self.vault_y.to_account_info())?;
+        self.close_vault(self.vault_lp.to_account_info())?;
+
+        emit!(PoolClosed {
+            config: self.config.key(),
+            authority: self.user.key(),
+            recipient: self.recipient.key(),
+        });
+        Ok(())
+    }
+
+    pub fn burn_vault(
+        &self,
+        from: AccountInfo<'info>,
+        mint: AccountInfo<'info>,
+        amount: u64,
+    ) -> Result<()> {
+        if amount == 0 {
+            return Ok(());
+        }
+
+        let cpi_account = Burn {
+            mint,
+            from,
+            authority: self.auth.to_account_info(),
+        };
+
+        let config_key = self.config.key();
+        let seeds = &[
+            &AUTH_SEED.as_ref()[..],
+            config_key.as_ref(),
+            &[self.config.auth_bump],
+        ];
+        let signer_seeds = &[&seeds[..]];
+        let cpi_context = CpiContext::new_with_signer(
+            self.token_program.to_account_info(),
+            cpi_account,
+            signer_seeds,
+        );
+        burn(cpi_context, amount)
+    }
+
+    pub fn transfer_vault(
+        &self,
+        from: AccountInfo<'info>,
+        to: AccountInfo<'info>,
+        mint: AccountInfo<'info>,
+        decimals: u8,
+        amount: u64,
+    ) -> Result<()> {
+        if amount == 0 {
+            return Ok(());
+        }
+
+        let cpi_account = TransferChecked {
+            from,
+            to,
+            authority: self.auth.to_account_info(),
+            mint,
+        };
+
+        let config_key = self.config.key();
+        let seeds = &[
+            &AUTH_SEED.as_ref()[..],
+            config_key.as_ref(),
+            &[self.config.auth_bump],
+        ];
+        let signer_seeds = &[&seeds[..]];
+        let cpi_context = CpiContext::new_with_signer(
+            self.token_program.to_account_info(),
+            cpi_account,
+            signer_seeds,
+        );
+        transfer_checked(cpi_context, amount, decimals)
+    }
+
+    pub fn close_vault(&self, account: AccountInfo<'info>) -> Result<()> {
+        let cpi_account = CloseAccount {
+            account,
+            destination: self.recipient.to_account_info(),
+            authority: self.auth.to_account_info(),
+        };
+
+        let config_key = self.config.key();
+        let seeds = &[
+            &AUTH_SEED.as_ref()[..],
+            config_key.as_ref(),
+            &[self.config.auth_bump],
+        ];
+        let signer_seeds = &[&seeds[..]];
+        let cpi_context = CpiContext::new_with_signer(
+            self.token_program.to_account_info(),
+            cpi_account,
+            signer_seeds,
+        );
+        close_account(cpi_context)
+    }
+}
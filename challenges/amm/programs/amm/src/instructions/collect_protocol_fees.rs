@@ -0,0 +1,122 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{
+    transfer_checked, Mint, TokenAccount, TokenInterface, TransferChecked,
+};
+
+use crate::{
+    error::AmmError, has_update_authority, Config, ProtocolFeesCollected, AUTH_SEED, CONFIG_SEED,
+};
+
+#[derive(Accounts)]
+pub struct CollectProtocolFees<'info> {
+    pub user: Signer<'info>,
+    pub mint_x: Box<InterfaceAccount<'info, Mint>>,
+    pub mint_y: Box<InterfaceAccount<'info, Mint>>,
+    #[account(
+        mut,
+        associated_token::mint = mint_x,
+        associated_token::authority = auth,
+        associated_token::token_program = token_program,
+    )]
+    pub vault_x: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        mut,
+        associated_token::mint = mint_y,
+        associated_token::authority = auth,
+        associated_token::token_program = token_program,
+    )]
+    pub vault_y: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        mut,
+        token::mint = mint_x,
+    )]
+    pub treasury_x: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        mut,
+        token::mint = mint_y,
+    )]
+    pub treasury_y: Box<InterfaceAccount<'info, TokenAccount>>,
+    /// CHECK: This is safe because it's just used to sign
+    #[account(
+        seeds = [AUTH_SEED.as_ref(), config.key().as_ref()],
+        bump = config.auth_bump
+    )]
+    pub auth: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        has_one = mint_x,
+        has_one = mint_y,
+        seeds = [CONFIG_SEED.as_ref(), config.seed.to_le_bytes().as_ref()],
+        bump = config.config_bump
+    )]
+    pub config: Box<Account<'info, Config>>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+impl<'info> CollectProtocolFees<'info> {
+    pub fn collect(&mut self) -> Result<()> {
+        has_update_authority!(self);
+        // The borrowed amount is still out of the vaults until the loan is repaid
+        require!(self.config.flash_loan == 0, AmmError::FlashLoanNotRepaid);
+
+        let (amount_x, amount_y) = (self.config.protocol_fees_x, self.config.protocol_fees_y);
+        require!(amount_x > 0 || amount_y > 0, AmmError::ZeroBalance);
+
+        if amount_x > 0 {
+            self.withdraw_fees(true, amount_x)?;
+        }
+        if amount_y > 0 {
+            self.withdraw_fees(false, amount_y)?;
+        }
+
+        self.config.protocol_fees_x = 0;
+        self.config.protocol_fees_y = 0;
+
+        emit!(ProtocolFeesCollected {
+            config: self.config.key(),
+            treasury_x: self.treasury_x.key(),
+            treasury_y: self.treasury_y.key(),
+            amount_x,
+            amount_y,
+        });
+        Ok(())
+    }
+
+    pub fn withdraw_fees(&self, is_x: bool, amount: u64) -> Result<()> {
+        let (from, to, mint, decimals) = match is_x {
+            true => (
+                self.vault_x.to_account_info(),
+                self.treasury_x.to_account_info(),
+                self.mint_x.to_account_info(),
+                self.mint_x.decimals,
+            ),
+            false => (
+                self.vault_y.to_account_info(),
+                self.treasury_y.to_account_info(),
+                self.mint_y.to_account_info(),
+                self.mint_y.decimals,
+            ),
+        };
+
+        let cpi_account = TransferChecked {
+            from,
+            to,
+            authority: self.auth.to_account_info(),
+            mint,
+        };
+
+        let config_key = self.config.key();
+        let seeds = &[
+            &AUTH_SEED.as_ref()[..],
+            config_key.as_ref(),
+            &[self.config.auth_bump],
+        ];
+        let signer_seeds = &[&seeds[..]];
+        let cpi_context = CpiContext::new_with_signer(
+            self.token_program.to_account_info(),
+            cpi_account,
+            signer_seeds,
+        );
+        transfer_checked(cpi_context, amount, decimals)
+    }
+}
@@ -0,0 +1,61 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::Mint;
+
+use crate::{
+    assert_not_locked, error::AmmError, Config, Pair, PairContested, CONFIG_SEED, LP_SEED,
+    PAIR_SEED,
+};
+
+#[derive(Accounts)]
+pub struct ContestPair<'info> {
+    pub user: Signer<'info>,
+    pub mint_x: Box<InterfaceAccount<'info, Mint>>,
+    pub mint_y: Box<InterfaceAccount<'info, Mint>>,
+    #[account(
+        has_one = mint_x,
+        has_one = mint_y,
+        seeds = [CONFIG_SEED.as_ref(), config.seed.to_le_bytes().as_ref()],
+        bump = config.config_bump
+    )]
+    pub config: Box<Account<'info, Config>>,
+    #[account(
+        seeds = [LP_SEED.as_ref(), config.key().as_ref()],
+        bump = config.lp_bump
+    )]
+    pub mint_lp: Box<InterfaceAccount<'info, Mint>>,
+    /// CHECK: This is safe because it's only compared to the pair entry, the pool may be closed
+    #[account(address = pair.config @ AmmError::InvalidPair)]
+    pub current: UncheckedAccount<'info>,
+    // Outlives the pool, `close_pool` burns the supply down to zero first
+    #[account(
+        seeds = [LP_SEED.as_ref(), current.key().as_ref()],
+        bump
+    )]
+    pub current_mint_lp: Box<InterfaceAccount<'info, Mint>>,
+    #[account(
+        mut,
+        has_one = mint_x,
+        has_one = mint_y,
+        seeds = [PAIR_SEED.as_ref(), mint_x.key().as_ref(), mint_y.key().as_ref()],
+        bump = pair.bump
+    )]
+    pub pair: Box<Account<'info, Pair>>,
+}
+
+impl<'info> ContestPair<'info> {
+    // A pair can only be taken over once its pool holds no liquidity, either closed or never
+    // funded. Comparing live reserves would let a flash deposit take the entry for one transaction
+    pub fn contest_pair(&mut self) -> Result<()> {
+        assert_not_locked!(self.config.locked);
+        require!(self.current_mint_lp.supply == 0, AmmError::PairInUse);
+        require!(self.mint_lp.supply > 0, AmmError::NoLiquidityInPool);
+
+        self.pair.config = self.config.key();
+        emit!(PairContested {
+            pair: self.pair.key(),
+            old_config: self.current.key(),
+            new_config: self.config.key(),
+        });
+        Ok(())
+    }
+}
@@ -0,0 +1,136 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    metadata::{
+        create_metadata_accounts_v3, mpl_token_metadata::types::DataV2, CreateMetadataAccountsV3,
+        Metadata, MetadataAccount,
+    },
+    token_interface::Mint,
+};
+
+use crate::{Config, AUTH_SEED, CONFIG_SEED, LP_SEED, METADATA_SEED};
+
+// Metaplex limits on the metadata strings, in bytes
+const MAX_NAME_LENGTH: usize = 32;
+const MAX_SYMBOL_LENGTH: usize = 10;
+
+#[derive(Accounts)]
+pub struct CreateLpMetadata<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub mint_x: Box<InterfaceAccount<'info, Mint>>,
+    pub mint_y: Box<InterfaceAccount<'info, Mint>>,
+    #[account(
+        seeds = [LP_SEED.as_ref(), config.key().as_ref()],
+        bump = config.lp_bump
+    )]
+    pub mint_lp: Box<InterfaceAccount<'info, Mint>>,
+    /// CHECK: Metadata of mint_x if it has any, only read for its symbol
+    #[account(
+        seeds = [METADATA_SEED.as_ref(), metadata_program.key().as_ref(), mint_x.key().as_ref()],
+        seeds::program = metadata_program.key(),
+        bump
+    )]
+    pub metadata_x: UncheckedAccount<'info>,
+    /// CHECK: Metadata of mint_y if it has any, only read for its symbol
+    #[account(
+        seeds = [METADATA_SEED.as_ref(), metadata_program.key().as_ref(), mint_y.key().as_ref()],
+        seeds::program = metadata_program.key(),
+        bump
+    )]
+    pub metadata_y: UncheckedAccount<'info>,
+    /// CHECK: Created by the token metadata program
+    #[account(
+        mut,
+        seeds = [METADATA_SEED.as_ref(), metadata_program.key().as_ref(), mint_lp.key().as_ref()],
+        seeds::program = metadata_program.key(),
+        bump
+    )]
+    pub metadata_lp: UncheckedAccount<'info>,
+    /// CHECK: This is safe because it's just used to sign
+    #[account(
+        seeds = [AUTH_SEED.as_ref(), config.key().as_ref()],
+        bump = config.auth_bump
+    )]
+    pub auth: UncheckedAccount<'info>,
+    #[account(
+        has_one = mint_x,
+        has_one = mint_y,
+        seeds = [CONFIG_SEED.as_ref(), config.seed.to_le_bytes().as_ref()],
+        bump = config.config_bump
+    )]
+    pub config: Box<Account<'info, Config>>,
+    pub metadata_program: Program<'info, Metadata>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+impl<'info> CreateLpMetadata<'info> {
+    // Anyone can create it, the name and symbol only depend on the pair
+    pub fn create_lp_metadata(&self) -> Result<()> {
+        let symbol_x = self.symbol(&self.metadata_x, &self.mint_x.key())?;
+        let symbol_y = self.symbol(&self.metadata_y, &self.mint_y.key())?;
+        let name = format!("{}-{} LP", symbol_x, symbol_y);
+        let symbol = format!("{}-{}", symbol_x, symbol_y);
+
+        let cpi_account = CreateMetadataAccountsV3 {
+            metadata: self.metadata_lp.to_account_info(),
+            mint: self.mint_lp.to_account_info(),
+            mint_authority: self.auth.to_account_info(),
+            payer: self.payer.to_account_info(),
+            update_authority: self.auth.to_account_info(),
+            system_program: self.system_program.to_account_info(),
+            rent: self.rent.to_account_info(),
+        };
+
+        let config_key = self.config.key();
+        let seeds = &[
+            &AUTH_SEED.as_ref()[..],
+            config_key.as_ref(),
+            &[self.config.auth_bump],
+        ];
+        let signer_seeds = &[&seeds[..]];
+        let cpi_context = CpiContext::new_with_signer(
+            self.metadata_program.to_account_info(),
+            cpi_account,
+            signer_seeds,
+        );
+        create_metadata_accounts_v3(
+            cpi_context,
+            DataV2 {
+                name: truncate(&name, MAX_NAME_LENGTH),
+                symbol: truncate(&symbol, MAX_SYMBOL_LENGTH),
+                uri: String::new(),
+                seller_fee_basis_points: 0,
+                creators: None,
+                collection: None,
+                uses: None,
+            },
+            true,
+            true,
+            None,
+        )
+    }
+
+    // Symbol from the mint's metadata, or the start of its address when it has none
+    pub fn symbol(&self, metadata: &AccountInfo<'info>, mint: &Pubkey) -> Result<String> {
+        if metadata.owner == &self.metadata_program.key() {
+            let data = metadata.try_borrow_data()?;
+            if let Ok(metadata) = MetadataAccount::try_deserialize(&mut &data[..]) {
+                let symbol = metadata.symbol.trim_end_matches('\0').trim();
+                if !symbol.is_empty() {
+                    return Ok(symbol.to_string());
+                }
+            }
+        }
+        Ok(mint.to_string().chars().take(4).collect())
+    }
+}
+
+// Longest prefix of `s` that fits in `max` bytes without splitting a character
+fn truncate(s: &str, max: usize) -> String {
+    let mut end = s.len().min(max);
+    while !s.is_char_boundary(end) {
+        end -= 1;
+    }
+    s[..end].to_string()
+}
@@ -5,11 +5,15 @@ use anchor_spl::{
         mint_to, transfer_checked, Mint, MintTo, TokenAccount, TokenInterface, TransferChecked,
     },
 };
-use constant_product_curve::ConstantProduct;
+use constant_product_curve::{ConstantProduct, LiquidityPair};
 
 use crate::{
-    assert_non_zero, assert_not_expired, assert_not_locked, error::AmmError, Config, AUTH_SEED,
-    CONFIG_SEED, LP_SEED,
+    assert_allowlisted, assert_non_zero, assert_not_expired, assert_not_locked,
+    error::AmmError,
+    helpers::{amount_after_fee, amount_before_fee, single_sided_swap_amount},
+    quote::Pool,
+    AllowlistEntry, Config, CurveType, LiquidityAdded, Oracle, Stats, ALLOWLIST_SEED, AUTH_SEED,
+    CONFIG_SEED, LP_SEED, MINIMUM_LIQUIDITY, ORACLE_SEED, STATS_SEED,
 };
 
 #[derive(Accounts)]
@@ -28,24 +32,28 @@ pub struct Deposit<'info> {
         mut,
         associated_token::mint = mint_x,
         associated_token::authority = auth,
+        associated_token::token_program = token_program,
     )]
     pub vault_x: Box<InterfaceAccount<'info, TokenAccount>>,
     #[account(
         mut,
         associated_token::mint = mint_y,
         associated_token::authority = auth,
+        associated_token::token_program = token_program,
     )]
     pub vault_y: Box<InterfaceAccount<'info, TokenAccount>>,
     #[account(
         mut,
         associated_token::mint = mint_x,
         associated_token::authority = user,
+        associated_token::token_program = token_program,
     )]
     pub user_x: Box<InterfaceAccount<'info, TokenAccount>>,
     #[account(
         mut,
         associated_token::mint = mint_y,
         associated_token::authority = user,
+        associated_token::token_program = token_program,
     )]
     pub user_y: Box<InterfaceAccount<'info, TokenAccount>>,
     #[account(
@@ -53,18 +61,49 @@ pub struct Deposit<'info> {
         payer = user,
         associated_token::mint = mint_lp,
         associated_token::authority = user,
+        associated_token::token_program = token_program,
     )]
     pub user_lp: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        mut,
+        associated_token::mint = mint_lp,
+        associated_token::authority = auth,
+        associated_token::token_program = token_program,
+    )]
+    pub vault_lp: Box<InterfaceAccount<'info, TokenAccount>>,
     /// CHECK: This is safe because it's just used to sign
-    #[account(seeds = [AUTH_SEED.as_ref()], bump = config.auth_bump)]
+    #[account(
+        seeds = [AUTH_SEED.as_ref(), config.key().as_ref()],
+        bump = config.auth_bump
+    )]
     pub auth: UncheckedAccount<'info>,
     #[account(
+        mut,
         has_one = mint_x,
         has_one = mint_y,
         seeds = [CONFIG_SEED.as_ref(), config.seed.to_le_bytes().as_ref()],
         bump = config.config_bump
     )]
     pub config: Box<Account<'info, Config>>,
+    #[account(
+        mut,
+        has_one = config,
+        seeds = [ORACLE_SEED.as_ref(), config.key().as_ref()],
+        bump = oracle.bump
+    )]
+    pub oracle: Box<Account<'info, Oracle>>,
+    #[account(
+        mut,
+        has_one = config,
+        seeds = [STATS_SEED.as_ref(), config.key().as_ref()],
+        bump = stats.bump
+    )]
+    pub stats: Box<Account<'info, Stats>>,
+    #[account(
+        seeds = [ALLOWLIST_SEED.as_ref(), config.key().as_ref(), user.key().as_ref()],
+        bump = allowlist_entry.bump
+    )]
+    pub allowlist_entry: Option<Box<Account<'info, AllowlistEntry>>>,
     pub associated_token_program: Program<'info, AssociatedToken>,
     pub token_program: Interface<'info, TokenInterface>,
     pub system_program: Program<'info, System>,
@@ -72,40 +111,162 @@ pub struct Deposit<'info> {
 
 impl<'info> Deposit<'info> {
     pub fn deposit(
-        &self,
+        &mut self,
         amount: u64, // Amount of LP token to claim
         max_x: u64,  // Max amount of X we are willing to deposit
         max_y: u64,  // Max amount of Y we are willing to deposit
         expiration: i64,
     ) -> Result<()> {
         assert_not_locked!(self.config.locked);
+        assert_allowlisted!(self);
         assert_not_expired!(expiration);
         assert_non_zero!([amount, max_x, max_y]);
+        self.update_oracle()?;
 
-        let (x, y) = match self.mint_lp.supply == 0
-            && self.vault_x.amount == 0
-            && self.vault_y.amount == 0
-        {
-            true => (max_x, max_y),
-            false => {
-                let amount = ConstantProduct::xy_deposit_amounts_from_l(
-                    self.vault_x.amount,
-                    self.vault_y.amount,
-                    self.mint_lp.supply,
-                    amount,
-                    6,
-                )
-                .map_err(AmmError::from)?;
-                (amount.x, amount.y)
+        // Donations sitting in the vaults before the first deposit go to its LP
+        let first_deposit = self.mint_lp.supply == 0;
+
+        // What the vaults receive out of the maximums once transfer fees are taken
+        let received_x = amount_after_fee(&self.mint_x.to_account_info(), max_x)?;
+        let received_y = amount_after_fee(&self.mint_y.to_account_info(), max_y)?;
+        let quote = self
+            .pool(Clock::get()?.unix_timestamp)
+            .deposit(amount, received_x, received_y)?;
+
+        // Amounts the user sends, so that the vaults receive their share after transfer fees
+        let (x, y) = match first_deposit {
+            true => {
+                // Initial LP supply is sqrt(x * y), or D on a StableSwap curve, part of it is locked
+                require!(quote.lp_amount >= amount, AmmError::SlippageExceeded);
+                (max_x, max_y)
             }
+            false => (
+                amount_before_fee(&self.mint_x.to_account_info(), quote.amount_x)?,
+                amount_before_fee(&self.mint_y.to_account_info(), quote.amount_y)?,
+            ),
         };
+        let amount = quote.lp_amount;
 
         // Check for slippage
         require!(x <= max_x && y <= max_y, AmmError::SlippageExceeded);
 
         self.deposit_tokens(true, x)?;
         self.deposit_tokens(false, y)?;
-        self.mint_lp_tokens(amount)
+        if first_deposit {
+            self.mint_lp_to(self.vault_lp.to_account_info(), MINIMUM_LIQUIDITY)?;
+        }
+        self.mint_lp_tokens(amount)?;
+        self.stats.record_deposit(x, y);
+
+        emit!(LiquidityAdded {
+            config: self.config.key(),
+            user: self.user.key(),
+            amount_x: x,
+            amount_y: y,
+            lp_amount: amount,
+        });
+        Ok(())
+    }
+
+    pub fn deposit_single(
+        &mut self,
+        is_x: bool,
+        amount_in: u64, // Amount of X or Y we deposit
+        min_lp: u64,    // Min amount of LP token we are willing to claim
+        expiration: i64,
+    ) -> Result<()> {
+        assert_not_locked!(self.config.locked);
+        assert_allowlisted!(self);
+        assert_not_expired!(expiration);
+        assert_non_zero!([amount_in, min_lp]);
+        require!(self.mint_lp.supply > 0, AmmError::NoLiquidityInPool);
+        // The swap split is solved for the constant product curve only
+        require!(
+            self.config.curve == CurveType::ConstantProduct,
+            AmmError::UnsupportedCurve
+        );
+        self.update_oracle()?;
+
+        let (x, y) = self
+            .config
+            .reserves(self.vault_x.amount, self.vault_y.amount)?;
+        let (reserve_in, reserve_out) = match is_x {
+            true => (x, y),
+            false => (y, x),
+        };
+
+        let mint_in = match is_x {
+            true => self.mint_x.to_account_info(),
+            false => self.mint_y.to_account_info(),
+        };
+
+        // Only what reaches the vault after the transfer fee is added to the pool
+        let received = amount_after_fee(&mint_in, amount_in)?;
+        let now = Clock::get()?.unix_timestamp;
+        let fee = self.config.effective_fee(now);
+        let swap_amount = single_sided_swap_amount(reserve_in, reserve_out, received, fee)?;
+        assert_non_zero!([swap_amount]);
+
+        let p = match is_x {
+            true => LiquidityPair::X,
+            false => LiquidityPair::Y,
+        };
+
+        // Swap part of the input against the pool without moving it out of the vaults
+        let mut curve =
+            ConstantProduct::init(x, y, self.mint_lp.supply, fee, None).map_err(AmmError::from)?;
+        let res = curve.swap(p, swap_amount, 1).map_err(AmmError::from)?;
+        assert_non_zero!([res.deposit, res.withdraw]);
+        let price_move = self.pool(now).price_move(is_x, res.deposit, res.withdraw)?;
+        self.config.update_volatility(price_move, now);
+        self.config.accrue_protocol_fee(is_x, res.fee)?;
+
+        // Mint LP for the leftover input and the swap output against the post-swap reserves
+        let reserve_in = reserve_in as u128 + res.deposit as u128;
+        let reserve_out = reserve_out as u128 - res.withdraw as u128;
+        let supply = self.mint_lp.supply as u128;
+        let lp_in = (received - res.deposit) as u128 * supply / reserve_in;
+        let lp_out = res.withdraw as u128 * supply / reserve_out;
+        let amount = u64::try_from(lp_in.min(lp_out)).map_err(|_| AmmError::Overflow)?;
+
+        // Check for slippage
+        require!(amount >= min_lp, AmmError::SlippageExceeded);
+
+        self.deposit_tokens(is_x, amount_in)?;
+        self.mint_lp_tokens(amount)?;
+
+        let (amount_x, amount_y) = match is_x {
+            true => (amount_in, 0),
+            false => (0, amount_in),
+        };
+        self.stats
+            .record_swap(is_x, res.deposit, res.withdraw, res.fee, now);
+        self.stats.record_deposit(amount_x, amount_y);
+        emit!(LiquidityAdded {
+            config: self.config.key(),
+            user: self.user.key(),
+            amount_x,
+            amount_y,
+            lp_amount: amount,
+        });
+        Ok(())
+    }
+
+    pub fn pool(&self, now: i64) -> Pool<'_> {
+        Pool {
+            config: &self.config,
+            vault_x: self.vault_x.amount,
+            vault_y: self.vault_y.amount,
+            lp_supply: self.mint_lp.supply,
+            now,
+        }
+    }
+
+    pub fn update_oracle(&mut self) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let prices = self.pool(now).oracle_prices()?;
+        self.oracle.update(prices, now);
+        Ok(())
     }
 
     pub fn deposit_tokens(&self, is_x: bool, amount: u64) -> Result<()> {
@@ -136,13 +297,22 @@ impl<'info> Deposit<'info> {
     }
 
     pub fn mint_lp_tokens(&self, amount: u64) -> Result<()> {
+        self.mint_lp_to(self.user_lp.to_account_info(), amount)
+    }
+
+    pub fn mint_lp_to(&self, to: AccountInfo<'info>, amount: u64) -> Result<()> {
         let cpi_account = MintTo {
             mint: self.mint_lp.to_account_info(),
-            to: self.user_lp.to_account_info(),
+            to,
             authority: self.auth.to_account_info(),
         };
 
-        let seeds = &[&AUTH_SEED.as_ref()[..], &[self.config.auth_bump]];
+        let config_key = self.config.key();
+        let seeds = &[
+            &AUTH_SEED.as_ref()[..],
+            config_key.as_ref(),
+            &[self.config.auth_bump],
+        ];
         let signer_seeds = &[&seeds[..]];
         let cpi_context = CpiContext::new_with_signer(
             self.token_program.to_account_info(),
@@ -0,0 +1,145 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{transfer_checked, Mint, TokenAccount, TokenInterface, TransferChecked},
+};
+
+use crate::{
+    error::AmmError,
+    has_update_authority,
+    helpers::{amount_after_fee, assert_supported_mint},
+    Config, Farm, FarmFunded, CONFIG_SEED, FARM_SEED, LP_SEED,
+};
+
+#[derive(Accounts)]
+pub struct InitFarm<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+    #[account(
+        seeds = [LP_SEED.as_ref(), config.key().as_ref()],
+        bump = config.lp_bump
+    )]
+    pub mint_lp: Box<InterfaceAccount<'info, Mint>>,
+    pub reward_mint: Box<InterfaceAccount<'info, Mint>>,
+    #[account(
+        init,
+        payer = user,
+        seeds = [FARM_SEED.as_ref(), config.key().as_ref()],
+        bump,
+        space = Farm::LEN
+    )]
+    pub farm: Box<Account<'info, Farm>>,
+    #[account(
+        init,
+        payer = user,
+        associated_token::mint = mint_lp,
+        associated_token::authority = farm,
+        associated_token::token_program = token_program,
+    )]
+    pub farm_lp: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        init,
+        payer = user,
+        associated_token::mint = reward_mint,
+        associated_token::authority = farm,
+        associated_token::token_program = token_program,
+    )]
+    pub farm_reward: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        seeds = [CONFIG_SEED.as_ref(), config.seed.to_le_bytes().as_ref()],
+        bump = config.config_bump
+    )]
+    pub config: Box<Account<'info, Config>>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> InitFarm<'info> {
+    pub fn init_farm(&mut self, bump: u8) -> Result<()> {
+        has_update_authority!(self);
+        assert_supported_mint(&self.reward_mint.to_account_info())?;
+        self.farm.init(
+            self.config.key(),
+            self.reward_mint.key(),
+            Clock::get()?.unix_timestamp,
+            bump,
+        );
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct FundFarm<'info> {
+    pub user: Signer<'info>,
+    pub reward_mint: Box<InterfaceAccount<'info, Mint>>,
+    #[account(
+        mut,
+        has_one = config,
+        has_one = reward_mint,
+        seeds = [FARM_SEED.as_ref(), config.key().as_ref()],
+        bump = farm.bump
+    )]
+    pub farm: Box<Account<'info, Farm>>,
+    #[account(
+        mut,
+        associated_token::mint = reward_mint,
+        associated_token::authority = farm,
+        associated_token::token_program = token_program,
+    )]
+    pub farm_reward: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        mut,
+        token::mint = reward_mint,
+        token::authority = user,
+    )]
+    pub user_reward: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        seeds = [CONFIG_SEED.as_ref(), config.seed.to_le_bytes().as_ref()],
+        bump = config.config_bump
+    )]
+    pub config: Box<Account<'info, Config>>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+impl<'info> FundFarm<'info> {
+    // Add `amount` rewards and emit from now on at `reward_rate` per second
+    pub fn fund(&mut self, amount: u64, reward_rate: u64) -> Result<()> {
+        has_update_authority!(self);
+
+        // Close out the old rate before switching to the new one
+        self.farm.update(Clock::get()?.unix_timestamp)?;
+
+        if amount > 0 {
+            require!(
+                amount <= self.user_reward.amount,
+                AmmError::InsufficientBalance
+            );
+            let cpi_account = TransferChecked {
+                from: self.user_reward.to_account_info(),
+                to: self.farm_reward.to_account_info(),
+                authority: self.user.to_account_info(),
+                mint: self.reward_mint.to_account_info(),
+            };
+            let cpi_context = CpiContext::new(self.token_program.to_account_info(), cpi_account);
+            transfer_checked(cpi_context, amount, self.reward_mint.decimals)?;
+
+            // Only what reaches the farm can be paid out
+            let received = amount_after_fee(&self.reward_mint.to_account_info(), amount)?;
+            self.farm.reward_remaining = self
+                .farm
+                .reward_remaining
+                .checked_add(received)
+                .ok_or(AmmError::Overflow)?;
+        }
+        self.farm.reward_rate = reward_rate;
+
+        emit!(FarmFunded {
+            config: self.config.key(),
+            farm: self.farm.key(),
+            amount,
+            reward_rate,
+        });
+        Ok(())
+    }
+}
@@ -0,0 +1,212 @@
+use anchor_lang::{
+    prelude::*,
+    solana_program::sysvar::instructions::{
+        load_current_index_checked, load_instruction_at_checked,
+    },
+    InstructionData,
+};
+use anchor_spl::token_interface::{
+    transfer_checked, Mint, TokenAccount, TokenInterface, TransferChecked,
+};
+
+use crate::{
+    assert_non_zero, assert_not_locked, error::AmmError, helpers::amount_before_fee, Config,
+    FlashLoanRepaid, AUTH_SEED, CONFIG_SEED, FLASH_LOAN_FEE,
+};
+
+// Position of `config` in the accounts of `flash_loan` and `flash_repay`
+const CONFIG_ACCOUNT_INDEX: usize = 8;
+
+#[derive(Accounts)]
+pub struct FlashLoan<'info> {
+    pub user: Signer<'info>,
+    pub mint_x: Box<InterfaceAccount<'info, Mint>>,
+    pub mint_y: Box<InterfaceAccount<'info, Mint>>,
+    #[account(
+        mut,
+        associated_token::mint = mint_x,
+        associated_token::authority = auth,
+        associated_token::token_program = token_program,
+    )]
+    pub vault_x: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        mut,
+        associated_token::mint = mint_y,
+        associated_token::authority = auth,
+        associated_token::token_program = token_program,
+    )]
+    pub vault_y: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        mut,
+        token::mint = mint_x,
+    )]
+    pub user_x: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        mut,
+        token::mint = mint_y,
+    )]
+    pub user_y: Box<InterfaceAccount<'info, TokenAccount>>,
+    /// CHECK: This is safe because it's just used to sign
+    #[account(
+        seeds = [AUTH_SEED.as_ref(), config.key().as_ref()],
+        bump = config.auth_bump
+    )]
+    pub auth: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        has_one = mint_x,
+        has_one = mint_y,
+        seeds = [CONFIG_SEED.as_ref(), config.seed.to_le_bytes().as_ref()],
+        bump = config.config_bump
+    )]
+    pub config: Box<Account<'info, Config>>,
+    /// CHECK: This is safe because the address is the instructions sysvar
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+    pub instructions: UncheckedAccount<'info>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+impl<'info> FlashLoan<'info> {
+    pub fn flash_loan(&mut self, is_x: bool, amount: u64) -> Result<()> {
+        assert_not_locked!(self.config.locked);
+        assert_non_zero!([amount]);
+        // Borrowers aren't checked against the allowlist
+        require!(!self.config.permissioned, AmmError::NotAllowlisted);
+
+        let (x, y) = self
+            .config
+            .reserves(self.vault_x.amount, self.vault_y.amount)?;
+        let reserve = match is_x {
+            true => x,
+            false => y,
+        };
+        require!(amount <= reserve, AmmError::InsufficientBalance);
+
+        self.assert_repaid_later()?;
+
+        // Deposits, swaps and withdrawals stay locked out until the loan is repaid
+        self.config.locked = true;
+        self.config.flash_loan = amount;
+        self.config.flash_loan_is_x = is_x;
+
+        self.lend(is_x, amount)
+    }
+
+    pub fn flash_repay(&mut self) -> Result<()> {
+        let (is_x, amount) = (self.config.flash_loan_is_x, self.config.flash_loan);
+        require!(amount > 0, AmmError::NoFlashLoan);
+
+        // Fee in basis points, rounded up
+        let fee = ((amount as u128 * FLASH_LOAN_FEE as u128 + 9999) / 10000) as u64;
+        let repay = amount.checked_add(fee).ok_or(AmmError::Overflow)?;
+
+        self.repay(is_x, repay)?;
+        self.config.accrue_protocol_fee(is_x, fee)?;
+
+        self.config.locked = false;
+        self.config.flash_loan = 0;
+        self.config.flash_loan_is_x = false;
+
+        emit!(FlashLoanRepaid {
+            config: self.config.key(),
+            user: self.user.key(),
+            is_x,
+            amount,
+            fee,
+        });
+        Ok(())
+    }
+
+    // The loan has to be taken at the top level and repaid further down the same transaction
+    pub fn assert_repaid_later(&self) -> Result<()> {
+        let instructions = self.instructions.to_account_info();
+        let current = load_current_index_checked(&instructions)? as usize;
+
+        let ix = load_instruction_at_checked(current, &instructions)?;
+        require_keys_eq!(ix.program_id, crate::ID, AmmError::FlashLoanCpi);
+
+        let repay_data = crate::instruction::FlashRepay {}.data();
+        let mut index = current + 1;
+        while let Ok(ix) = load_instruction_at_checked(index, &instructions) {
+            if ix.program_id == crate::ID
+                && ix.data == repay_data
+                && ix
+                    .accounts
+                    .get(CONFIG_ACCOUNT_INDEX)
+                    .map_or(false, |meta| meta.pubkey == self.config.key())
+            {
+                return Ok(());
+            }
+            index += 1;
+        }
+
+        err!(AmmError::FlashLoanNotRepaid)
+    }
+
+    pub fn lend(&self, is_x: bool, amount: u64) -> Result<()> {
+        let (from, to, mint, decimals) = match is_x {
+            true => (
+                self.vault_x.to_account_info(),
+                self.user_x.to_account_info(),
+                self.mint_x.to_account_info(),
+                self.mint_x.decimals,
+            ),
+            false => (
+                self.vault_y.to_account_info(),
+                self.user_y.to_account_info(),
+                self.mint_y.to_account_info(),
+                self.mint_y.decimals,
+            ),
+        };
+
+        let cpi_account = TransferChecked {
+            from,
+            to,
+            authority: self.auth.to_account_info(),
+            mint,
+        };
+
+        let config_key = self.config.key();
+        let seeds = &[
+            &AUTH_SEED.as_ref()[..],
+            config_key.as_ref(),
+            &[self.config.auth_bump],
+        ];
+        let signer_seeds = &[&seeds[..]];
+        let cpi_context = CpiContext::new_with_signer(
+            self.token_program.to_account_info(),
+            cpi_account,
+            signer_seeds,
+        );
+        transfer_checked(cpi_context, amount, decimals)
+    }
+
+    // Send enough for `amount` to reach the vault after any transfer fee
+    pub fn repay(&self, is_x: bool, amount: u64) -> Result<()> {
+        let (from, to, mint, decimals) = match is_x {
+            true => (
+                self.user_x.to_account_info(),
+                self.vault_x.to_account_info(),
+                self.mint_x.to_account_info(),
+                self.mint_x.decimals,
+            ),
+            false => (
+                self.user_y.to_account_info(),
+                self.vault_y.to_account_info(),
+                self.mint_y.to_account_info(),
+                self.mint_y.decimals,
+            ),
+        };
+        let amount = amount_before_fee(&mint, amount)?;
+
+        let cpi_account = TransferChecked {
+            from,
+            to,
+            authority: self.user.to_account_info(),
+            mint,
+        };
+
+        let cpi_context = CpiContext::new(self.token_program.to_account_info(), cpi_account);
+        transfer_checked(cpi_context, amount, decimals)
+    }
+}
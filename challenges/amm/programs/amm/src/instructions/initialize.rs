@@ -4,10 +4,13 @@ use anchor_spl::{
     token_interface::{Mint, TokenAccount, TokenInterface},
 };
 
-use crate::{error::AmmError, Config, AUTH_SEED, CONFIG_SEED, LP_SEED};
+use crate::{
+    error::AmmError, helpers::assert_supported_mint, Config, CurveType, Oracle, PoolInitialized,
+    Stats, AUTH_SEED, CONFIG_SEED, LP_SEED, MAX_LP_DECIMALS, ORACLE_SEED, STATS_SEED,
+};
 
 #[derive(Accounts)]
-#[instruction(seed: u64)]
+#[instruction(seed: u64, fee: u16, authority: Option<Pubkey>, curve: CurveType, amp: u64, lp_decimals: u8, permissioned: bool)]
 pub struct Initialize<'info> {
     #[account(mut)]
     pub initializer: Signer<'info>,
@@ -17,7 +20,7 @@ pub struct Initialize<'info> {
         init,
         seeds = [LP_SEED.as_ref(), config.key().as_ref()],
         payer = initializer,
-        mint::decimals = 6,
+        mint::decimals = lp_decimals,
         mint::authority = auth,
         mint::token_program = token_program,
         bump
@@ -29,6 +32,8 @@ pub struct Initialize<'info> {
         associated_token::mint = mint_x,
         associated_token::authority = auth,
         associated_token::token_program = token_program,
+        // Checked before `vault_y` is created, which would be this same account
+        constraint = mint_x.key() != mint_y.key() @ AmmError::IdenticalMints,
     )]
     pub vault_x: Box<InterfaceAccount<'info, TokenAccount>>,
     #[account(
@@ -39,8 +44,16 @@ pub struct Initialize<'info> {
         associated_token::token_program = token_program,
     )]
     pub vault_y: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        init,
+        payer = initializer,
+        associated_token::mint = mint_lp,
+        associated_token::authority = auth,
+        associated_token::token_program = token_program,
+    )]
+    pub vault_lp: Box<InterfaceAccount<'info, TokenAccount>>,
     /// CHECK: This is safe because it's just used to sign
-    #[account(seeds = [AUTH_SEED.as_ref()], bump)]
+    #[account(seeds = [AUTH_SEED.as_ref(), config.key().as_ref()], bump)]
     pub auth: UncheckedAccount<'info>,
     #[account(
         init,
@@ -50,23 +63,76 @@ pub struct Initialize<'info> {
         space = Config::LEN
     )]
     pub config: Box<Account<'info, Config>>,
+    #[account(
+        init,
+        payer = initializer,
+        seeds = [ORACLE_SEED.as_ref(), config.key().as_ref()],
+        bump,
+        space = Oracle::LEN
+    )]
+    pub oracle: Box<Account<'info, Oracle>>,
+    #[account(
+        init,
+        payer = initializer,
+        seeds = [STATS_SEED.as_ref(), config.key().as_ref()],
+        bump,
+        space = Stats::LEN
+    )]
+    pub stats: Box<Account<'info, Stats>>,
     pub associated_token_program: Program<'info, AssociatedToken>,
     pub token_program: Interface<'info, TokenInterface>,
     pub system_program: Program<'info, System>,
 }
 
-pub fn handler(ctx: Context<Initialize>, seed: u64, fee: u16, authority: Option<Pubkey>) -> Result<()> {
+pub fn handler(
+    ctx: Context<Initialize>,
+    seed: u64,
+    fee: u16,
+    authority: Option<Pubkey>,
+    curve: CurveType,
+    amp: u64,
+    lp_decimals: u8,
+    permissioned: bool,
+) -> Result<()> {
     // Don't charge >100.00% as a fee
     require!(fee <= 10000, AmmError::InvalidFee);
+    require!(lp_decimals <= MAX_LP_DECIMALS, AmmError::InvalidPrecision);
+    // Someone has to manage the allowlist
+    require!(
+        !permissioned || authority.is_some(),
+        AmmError::NoAuthoritySet
+    );
+    assert_supported_mint(&ctx.accounts.mint_x.to_account_info())?;
+    assert_supported_mint(&ctx.accounts.mint_y.to_account_info())?;
     ctx.accounts.config.init(
         seed,
         authority, 
+        permissioned,
         ctx.accounts.mint_x.key(), 
         ctx.accounts.mint_y.key(), 
         fee, 
+        lp_decimals,
         ctx.bumps.auth, 
         ctx.bumps.config, 
         ctx.bumps.mint_lp 
     );
+    let now = Clock::get()?.unix_timestamp;
+    ctx.accounts.config.init_curve(curve, amp, now)?;
+    ctx.accounts.oracle.init(ctx.accounts.config.key(), now, ctx.bumps.oracle);
+    ctx.accounts.stats.init(ctx.accounts.config.key(), ctx.bumps.stats);
+    emit!(PoolInitialized {
+        config: ctx.accounts.config.key(),
+        initializer: ctx.accounts.initializer.key(),
+        authority,
+        mint_x: ctx.accounts.mint_x.key(),
+        mint_y: ctx.accounts.mint_y.key(),
+        mint_lp: ctx.accounts.mint_lp.key(),
+        seed,
+        fee,
+        curve,
+        amp,
+        lp_decimals,
+        permissioned,
+    });
     Ok(())
 }
@@ -1,7 +1,33 @@
+pub mod allowlist;
+pub mod close_pool;
+pub mod collect_protocol_fees;
+pub mod contest_pair;
+pub mod create_lp_metadata;
 pub mod deposit;
+pub mod farm;
+pub mod flash_loan;
 pub mod initialize;
+pub mod observe;
+pub mod register_pair;
+pub mod route_swap;
+pub mod stake;
 pub mod swap;
+pub mod update;
+pub mod withdraw;
 
+pub use allowlist::*;
+pub use close_pool::*;
+pub use collect_protocol_fees::*;
+pub use contest_pair::*;
+pub use create_lp_metadata::*;
 pub use deposit::*;
+pub use farm::*;
+pub use flash_loan::*;
 pub use initialize::*;
+pub use observe::*;
+pub use register_pair::*;
+pub use route_swap::*;
+pub use stake::*;
 pub use swap::*;
+pub use update::*;
+pub use withdraw::*;
@@ -0,0 +1,58 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+use crate::{quote::Pool, Config, Oracle, Twap, AUTH_SEED, CONFIG_SEED, ORACLE_SEED};
+
+#[derive(Accounts)]
+pub struct Observe<'info> {
+    pub mint_x: Box<InterfaceAccount<'info, Mint>>,
+    pub mint_y: Box<InterfaceAccount<'info, Mint>>,
+    #[account(
+        associated_token::mint = mint_x,
+        associated_token::authority = auth,
+        associated_token::token_program = token_program,
+    )]
+    pub vault_x: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        associated_token::mint = mint_y,
+        associated_token::authority = auth,
+        associated_token::token_program = token_program,
+    )]
+    pub vault_y: Box<InterfaceAccount<'info, TokenAccount>>,
+    /// CHECK: This is safe because it's just used to derive the vaults
+    #[account(
+        seeds = [AUTH_SEED.as_ref(), config.key().as_ref()],
+        bump = config.auth_bump
+    )]
+    pub auth: UncheckedAccount<'info>,
+    #[account(
+        has_one = mint_x,
+        has_one = mint_y,
+        seeds = [CONFIG_SEED.as_ref(), config.seed.to_le_bytes().as_ref()],
+        bump = config.config_bump
+    )]
+    pub config: Box<Account<'info, Config>>,
+    #[account(
+        has_one = config,
+        seeds = [ORACLE_SEED.as_ref(), config.key().as_ref()],
+        bump = oracle.bump
+    )]
+    pub oracle: Box<Account<'info, Oracle>>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+impl<'info> Observe<'info> {
+    pub fn observe(&self, window: u32) -> Result<Twap> {
+        let now = Clock::get()?.unix_timestamp;
+        // The LP supply doesn't move the spot price
+        let prices = Pool {
+            config: &self.config,
+            vault_x: self.vault_x.amount,
+            vault_y: self.vault_y.amount,
+            lp_supply: 0,
+            now,
+        }
+        .oracle_prices()?;
+        self.oracle.twap(prices, now, window)
+    }
+}
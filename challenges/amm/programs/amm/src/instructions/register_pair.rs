@@ -0,0 +1,50 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::Mint;
+
+use crate::{error::AmmError, Config, Pair, PairRegistered, CONFIG_SEED, PAIR_SEED};
+
+#[derive(Accounts)]
+pub struct RegisterPair<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+    pub mint_x: Box<InterfaceAccount<'info, Mint>>,
+    // Only pools created in canonical order can be registered, so each pair has a single PDA
+    #[account(constraint = mint_x.key() < mint_y.key() @ AmmError::UnsortedMints)]
+    pub mint_y: Box<InterfaceAccount<'info, Mint>>,
+    #[account(
+        has_one = mint_x,
+        has_one = mint_y,
+        seeds = [CONFIG_SEED.as_ref(), config.seed.to_le_bytes().as_ref()],
+        bump = config.config_bump
+    )]
+    pub config: Box<Account<'info, Config>>,
+    #[account(
+        init,
+        payer = user,
+        seeds = [PAIR_SEED.as_ref(), mint_x.key().as_ref(), mint_y.key().as_ref()],
+        bump,
+        space = Pair::LEN
+    )]
+    pub pair: Box<Account<'info, Pair>>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> RegisterPair<'info> {
+    // Anyone can claim a pair that has no entry yet, pools without an authority included.
+    // An entry whose pool is closed or was never funded is taken over with `contest_pair`
+    pub fn register_pair(&mut self, bump: u8) -> Result<()> {
+        self.pair.init(
+            self.mint_x.key(),
+            self.mint_y.key(),
+            self.config.key(),
+            bump,
+        );
+        emit!(PairRegistered {
+            pair: self.pair.key(),
+            config: self.config.key(),
+            mint_x: self.mint_x.key(),
+            mint_y: self.mint_y.key(),
+        });
+        Ok(())
+    }
+}
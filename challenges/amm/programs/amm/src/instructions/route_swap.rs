@@ -0,0 +1,323 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::get_associated_token_address_with_program_id,
+    token_interface::{transfer_checked, Mint, TokenAccount, TokenInterface, TransferChecked},
+};
+
+use crate::{
+    assert_non_zero, assert_not_expired, assert_not_locked, error::AmmError,
+    helpers::amount_after_fee, quote::Pool, Config, Oracle, Stats, Swapped, AUTH_SEED, CONFIG_SEED,
+    LP_SEED, ORACLE_SEED, STATS_SEED,
+};
+
+// config, auth, oracle, stats, mint_lp, vault_x, vault_y, mint_x, mint_y
+pub const HOP_ACCOUNTS: usize = 9;
+
+#[derive(Accounts)]
+pub struct RouteSwap<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+    #[account(
+        mut,
+        token::authority = user,
+    )]
+    pub user_in: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        mut,
+        token::authority = user,
+    )]
+    pub user_out: Box<InterfaceAccount<'info, TokenAccount>>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+// One pool along the route, loaded from `remaining_accounts` and checked against its `Config`
+pub struct Hop<'info> {
+    pub config: Account<'info, Config>,
+    pub oracle: Account<'info, Oracle>,
+    pub stats: Account<'info, Stats>,
+    pub auth: AccountInfo<'info>,
+    pub mint_lp: InterfaceAccount<'info, Mint>,
+    pub vault_x: InterfaceAccount<'info, TokenAccount>,
+    pub vault_y: InterfaceAccount<'info, TokenAccount>,
+    pub mint_x: InterfaceAccount<'info, Mint>,
+    pub mint_y: InterfaceAccount<'info, Mint>,
+}
+
+impl<'info> Hop<'info> {
+    pub fn load(accounts: &'info [AccountInfo<'info>], program_id: &Pubkey) -> Result<Self> {
+        let config = Account::<Config>::try_from(&accounts[0])?;
+        let config_key = config.key();
+
+        let expected = Pubkey::create_program_address(
+            &[
+                CONFIG_SEED.as_ref(),
+                config.seed.to_le_bytes().as_ref(),
+                &[config.config_bump],
+            ],
+            program_id,
+        )
+        .map_err(|_| AmmError::BumpError)?;
+        require_keys_eq!(expected, config_key, AmmError::InvalidRoute);
+
+        let expected = Pubkey::create_program_address(
+            &[AUTH_SEED.as_ref(), config_key.as_ref(), &[config.auth_bump]],
+            program_id,
+        )
+        .map_err(|_| AmmError::BumpError)?;
+        require_keys_eq!(expected, accounts[1].key(), AmmError::InvalidRoute);
+
+        let oracle = Account::<Oracle>::try_from(&accounts[2])?;
+        let expected = Pubkey::create_program_address(
+            &[ORACLE_SEED.as_ref(), config_key.as_ref(), &[oracle.bump]],
+            program_id,
+        )
+        .map_err(|_| AmmError::BumpError)?;
+        require_keys_eq!(expected, oracle.key(), AmmError::InvalidRoute);
+        require_keys_eq!(oracle.config, config_key, AmmError::InvalidRoute);
+
+        let stats = Account::<Stats>::try_from(&accounts[3])?;
+        let expected = Pubkey::create_program_address(
+            &[STATS_SEED.as_ref(), config_key.as_ref(), &[stats.bump]],
+            program_id,
+        )
+        .map_err(|_| AmmError::BumpError)?;
+        require_keys_eq!(expected, stats.key(), AmmError::InvalidRoute);
+        require_keys_eq!(stats.config, config_key, AmmError::InvalidRoute);
+
+        let mint_lp = InterfaceAccount::<Mint>::try_from(&accounts[4])?;
+        let expected = Pubkey::create_program_address(
+            &[LP_SEED.as_ref(), config_key.as_ref(), &[config.lp_bump]],
+            program_id,
+        )
+        .map_err(|_| AmmError::BumpError)?;
+        require_keys_eq!(expected, mint_lp.key(), AmmError::InvalidRoute);
+
+        let vault_x = InterfaceAccount::<TokenAccount>::try_from(&accounts[5])?;
+        let vault_y = InterfaceAccount::<TokenAccount>::try_from(&accounts[6])?;
+        let mint_x = InterfaceAccount::<Mint>::try_from(&accounts[7])?;
+        let mint_y = InterfaceAccount::<Mint>::try_from(&accounts[8])?;
+
+        require_keys_eq!(mint_x.key(), config.mint_x, AmmError::InvalidToken);
+        require_keys_eq!(mint_y.key(), config.mint_y, AmmError::InvalidToken);
+        require_keys_eq!(vault_x.mint, config.mint_x, AmmError::InvalidToken);
+        require_keys_eq!(vault_y.mint, config.mint_y, AmmError::InvalidToken);
+
+        // Only the pool's own ATAs, any other account owned by auth would misprice the pool
+        for (vault, mint) in [(&accounts[5], &accounts[7]), (&accounts[6], &accounts[8])] {
+            let expected = get_associated_token_address_with_program_id(
+                &accounts[1].key(),
+                mint.key,
+                mint.owner,
+            );
+            require_keys_eq!(expected, vault.key(), AmmError::InvalidRoute);
+        }
+
+        Ok(Self {
+            config,
+            oracle,
+            stats,
+            auth: accounts[1].clone(),
+            mint_lp,
+            vault_x,
+            vault_y,
+            mint_x,
+            mint_y,
+        })
+    }
+
+    // Vault and mint the tokens leave from or arrive in
+    pub fn side(&self, is_x: bool) -> (AccountInfo<'info>, AccountInfo<'info>, u8) {
+        match is_x {
+            true => (
+                self.vault_x.to_account_info(),
+                self.mint_x.to_account_info(),
+                self.mint_x.decimals,
+            ),
+            false => (
+                self.vault_y.to_account_info(),
+                self.mint_y.to_account_info(),
+                self.mint_y.decimals,
+            ),
+        }
+    }
+}
+
+impl<'info> RouteSwap<'info> {
+    pub fn route_swap(
+        &mut self,
+        remaining_accounts: &'info [AccountInfo<'info>],
+        program_id: &Pubkey,
+        hops: u8,
+        amount_in: u64,
+        min_out: u64,
+        expiration: i64,
+    ) -> Result<()> {
+        assert_not_expired!(expiration);
+        assert_non_zero!([amount_in]);
+        require!(
+            hops > 0 && remaining_accounts.len() == hops as usize * HOP_ACCOUNTS,
+            AmmError::InvalidRoute
+        );
+
+        let now = Clock::get()?.unix_timestamp;
+        let mut visited: Vec<Pubkey> = Vec::with_capacity(hops as usize);
+        let mut previous: Option<(Hop<'info>, bool)> = None;
+        let mut mint = self.user_in.mint;
+        let mut amount = amount_in;
+
+        for accounts in remaining_accounts.chunks(HOP_ACCOUNTS) {
+            let mut hop = Hop::load(accounts, program_id)?;
+            assert_not_locked!(hop.config.locked);
+            // Hops carry no allowlist entry, permissioned pools are swapped through `swap`
+            require!(!hop.config.permissioned, AmmError::NotAllowlisted);
+
+            // A pool can only be used once, its vaults are not reloaded between hops
+            require!(!visited.contains(&hop.config.key()), AmmError::InvalidRoute);
+            visited.push(hop.config.key());
+
+            let is_x = match mint {
+                m if m == hop.config.mint_x => true,
+                m if m == hop.config.mint_y => false,
+                _ => return err!(AmmError::InvalidToken),
+            };
+
+            let pool = Pool {
+                config: &hop.config,
+                vault_x: hop.vault_x.amount,
+                vault_y: hop.vault_y.amount,
+                lp_supply: hop.mint_lp.supply,
+                now,
+            };
+            let (x, y) = pool.reserves()?;
+            let prices = pool.oracle_prices()?;
+            hop.oracle.update(prices, now);
+
+            // Only what reaches the vault after the transfer fee is priced
+            let (vault_in, mint_in, decimals_in) = hop.side(is_x);
+            let received = amount_after_fee(&mint_in, amount)?;
+            assert_non_zero!([received]);
+
+            // Slippage is only enforced on the final output
+            let res = hop
+                .config
+                .swap((x, y), hop.mint_lp.supply, is_x, received, 0, now)?;
+            assert_non_zero!([res.deposit, res.withdraw]);
+            let price_move = pool.price_move(is_x, res.deposit, res.withdraw)?;
+            hop.config.update_volatility(price_move, now);
+            hop.config.accrue_protocol_fee(is_x, res.fee)?;
+            hop.stats
+                .record_swap(is_x, res.deposit, res.withdraw, res.fee, now);
+
+            // Move the input into this pool, either from the user or from the previous pool
+            match &previous {
+                None => self.transfer(
+                    self.user_in.to_account_info(),
+                    vault_in,
+                    mint_in,
+                    decimals_in,
+                    self.user.to_account_info(),
+                    &[],
+                    amount,
+                )?,
+                Some((prev, prev_is_x)) => {
+                    let (vault_out, _, _) = prev.side(!prev_is_x);
+                    let config_key = prev.config.key();
+                    let seeds = &[
+                        &AUTH_SEED.as_ref()[..],
+                        config_key.as_ref(),
+                        &[prev.config.auth_bump],
+                    ];
+                    self.transfer(
+                        vault_out,
+                        vault_in,
+                        mint_in,
+                        decimals_in,
+                        prev.auth.clone(),
+                        &[&seeds[..]],
+                        amount,
+                    )?
+                }
+            }
+
+            let (reserve_x_after, reserve_y_after) = hop.config.reserves_after_swap(
+                hop.vault_x.amount,
+                hop.vault_y.amount,
+                is_x,
+                res.deposit,
+                res.withdraw,
+            )?;
+            emit!(Swapped {
+                config: hop.config.key(),
+                user: self.user.key(),
+                is_x,
+                amount_in: res.deposit,
+                amount_out: res.withdraw,
+                fee: res.fee,
+                reserve_x_before: x,
+                reserve_y_before: y,
+                reserve_x_after,
+                reserve_y_after,
+            });
+
+            hop.config.exit(program_id)?;
+            hop.oracle.exit(program_id)?;
+            hop.stats.exit(program_id)?;
+
+            mint = match is_x {
+                true => hop.config.mint_y,
+                false => hop.config.mint_x,
+            };
+            amount = res.withdraw;
+            previous = Some((hop, is_x));
+        }
+
+        require_keys_eq!(self.user_out.mint, mint, AmmError::InvalidToken);
+
+        let (last, last_is_x) = previous.ok_or(AmmError::InvalidRoute)?;
+        let (vault_out, mint_out, decimals_out) = last.side(!last_is_x);
+
+        // Check for slippage on what the user actually receives
+        let received = amount_after_fee(&mint_out, amount)?;
+        require!(received >= min_out, AmmError::SlippageExceeded);
+        let config_key = last.config.key();
+        let seeds = &[
+            &AUTH_SEED.as_ref()[..],
+            config_key.as_ref(),
+            &[last.config.auth_bump],
+        ];
+        self.transfer(
+            vault_out,
+            self.user_out.to_account_info(),
+            mint_out,
+            decimals_out,
+            last.auth.clone(),
+            &[&seeds[..]],
+            amount,
+        )
+    }
+
+    pub fn transfer(
+        &self,
+        from: AccountInfo<'info>,
+        to: AccountInfo<'info>,
+        mint: AccountInfo<'info>,
+        decimals: u8,
+        authority: AccountInfo<'info>,
+        signer_seeds: &[&[&[u8]]],
+        amount: u64,
+    ) -> Result<()> {
+        let cpi_account = TransferChecked {
+            from,
+            to,
+            authority,
+            mint,
+        };
+
+        let cpi_context = CpiContext::new_with_signer(
+            self.token_program.to_account_info(),
+            cpi_account,
+            signer_seeds,
+        );
+        transfer_checked(cpi_context, amount, decimals)
+    }
+}
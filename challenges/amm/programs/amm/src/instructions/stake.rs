@@ -0,0 +1,201 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{transfer_checked, Mint, TokenAccount, TokenInterface, TransferChecked},
+};
+
+use crate::{
+    assert_non_zero, error::AmmError, Config, Farm, LpStaked, LpUnstaked, RewardsClaimed,
+    StakePosition, CONFIG_SEED, FARM_SEED, LP_SEED, STAKE_SEED,
+};
+
+#[derive(Accounts)]
+pub struct Stake<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+    #[account(
+        seeds = [LP_SEED.as_ref(), config.key().as_ref()],
+        bump = config.lp_bump
+    )]
+    pub mint_lp: Box<InterfaceAccount<'info, Mint>>,
+    pub reward_mint: Box<InterfaceAccount<'info, Mint>>,
+    #[account(
+        mut,
+        has_one = config,
+        has_one = reward_mint,
+        seeds = [FARM_SEED.as_ref(), config.key().as_ref()],
+        bump = farm.bump
+    )]
+    pub farm: Box<Account<'info, Farm>>,
+    #[account(
+        mut,
+        associated_token::mint = mint_lp,
+        associated_token::authority = farm,
+        associated_token::token_program = token_program,
+    )]
+    pub farm_lp: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        mut,
+        associated_token::mint = reward_mint,
+        associated_token::authority = farm,
+        associated_token::token_program = token_program,
+    )]
+    pub farm_reward: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        mut,
+        associated_token::mint = mint_lp,
+        associated_token::authority = user,
+        associated_token::token_program = token_program,
+    )]
+    pub user_lp: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        init_if_needed,
+        payer = user,
+        associated_token::mint = reward_mint,
+        associated_token::authority = user,
+        associated_token::token_program = token_program,
+    )]
+    pub user_reward: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        init_if_needed,
+        payer = user,
+        seeds = [STAKE_SEED.as_ref(), farm.key().as_ref(), user.key().as_ref()],
+        bump,
+        space = StakePosition::LEN
+    )]
+    pub position: Box<Account<'info, StakePosition>>,
+    #[account(
+        seeds = [CONFIG_SEED.as_ref(), config.seed.to_le_bytes().as_ref()],
+        bump = config.config_bump
+    )]
+    pub config: Box<Account<'info, Config>>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> Stake<'info> {
+    pub fn stake_lp(&mut self, amount: u64, bump: u8) -> Result<()> {
+        assert_non_zero!([amount]);
+        require!(amount <= self.user_lp.amount, AmmError::InsufficientBalance);
+        self.settle(bump)?;
+
+        let cpi_account = TransferChecked {
+            from: self.user_lp.to_account_info(),
+            to: self.farm_lp.to_account_info(),
+            authority: self.user.to_account_info(),
+            mint: self.mint_lp.to_account_info(),
+        };
+        let cpi_context = CpiContext::new(self.token_program.to_account_info(), cpi_account);
+        transfer_checked(cpi_context, amount, self.mint_lp.decimals)?;
+
+        self.position.amount = self
+            .position
+            .amount
+            .checked_add(amount)
+            .ok_or(AmmError::Overflow)?;
+        self.farm.total_staked = self
+            .farm
+            .total_staked
+            .checked_add(amount)
+            .ok_or(AmmError::Overflow)?;
+
+        emit!(LpStaked {
+            config: self.config.key(),
+            user: self.user.key(),
+            amount,
+        });
+        Ok(())
+    }
+
+    pub fn unstake_lp(&mut self, amount: u64, bump: u8) -> Result<()> {
+        assert_non_zero!([amount]);
+        require!(
+            amount <= self.position.amount,
+            AmmError::InsufficientBalance
+        );
+        self.settle(bump)?;
+
+        self.transfer_from_farm(
+            self.farm_lp.to_account_info(),
+            self.user_lp.to_account_info(),
+            self.mint_lp.to_account_info(),
+            self.mint_lp.decimals,
+            amount,
+        )?;
+
+        self.position.amount -= amount;
+        self.farm.total_staked -= amount;
+
+        emit!(LpUnstaked {
+            config: self.config.key(),
+            user: self.user.key(),
+            amount,
+        });
+        Ok(())
+    }
+
+    pub fn claim_rewards(&mut self, bump: u8) -> Result<()> {
+        self.settle(bump)?;
+
+        let amount = self.position.pending;
+        assert_non_zero!([amount]);
+        self.position.pending = 0;
+
+        self.transfer_from_farm(
+            self.farm_reward.to_account_info(),
+            self.user_reward.to_account_info(),
+            self.reward_mint.to_account_info(),
+            self.reward_mint.decimals,
+            amount,
+        )?;
+
+        emit!(RewardsClaimed {
+            config: self.config.key(),
+            user: self.user.key(),
+            amount,
+        });
+        Ok(())
+    }
+
+    // Bring the farm up to date and credit the position with what it earned so far
+    pub fn settle(&mut self, bump: u8) -> Result<()> {
+        if self.position.owner == Pubkey::default() {
+            self.position.farm = self.farm.key();
+            self.position.owner = self.user.key();
+            self.position.bump = bump;
+        }
+        self.farm.update(Clock::get()?.unix_timestamp)?;
+        self.position.settle(self.farm.reward_per_share)
+    }
+
+    pub fn transfer_from_farm(
+        &self,
+        from: AccountInfo<'info>,
+        to: AccountInfo<'info>,
+        mint: AccountInfo<'info>,
+        decimals: u8,
+        amount: u64,
+    ) -> Result<()> {
+        let cpi_account = TransferChecked {
+            from,
+            to,
+            authority: self.farm.to_account_info(),
+            mint,
+        };
+
+        let config_key = self.config.key();
+        let seeds = &[
+            &FARM_SEED.as_ref()[..],
+            config_key.as_ref(),
+            &[self.farm.bump],
+        ];
+        let signer_seeds = &[&seeds[..]];
+        let cpi_context = CpiContext::new_with_signer(
+            self.token_program.to_account_info(),
+            cpi_account,
+            signer_seeds,
+        );
+        transfer_checked(cpi_context, amount, decimals)
+    }
+}
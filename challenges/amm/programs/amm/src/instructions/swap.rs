@@ -3,11 +3,14 @@ use anchor_spl::{
     associated_token::AssociatedToken,
     token_interface::{transfer_checked, Mint, TokenAccount, TokenInterface, TransferChecked},
 };
-use constant_product_curve::{ConstantProduct, LiquidityPair};
 
 use crate::{
-    assert_non_zero, assert_not_expired, assert_not_locked, error::AmmError, Config, AUTH_SEED,
-    CONFIG_SEED, LP_SEED,
+    assert_allowlisted, assert_non_zero, assert_not_expired, assert_not_locked,
+    error::AmmError,
+    helpers::{amount_after_fee, amount_before_fee},
+    quote::Pool,
+    AllowlistEntry, Config, Oracle, Stats, Swapped, ALLOWLIST_SEED, AUTH_SEED, CONFIG_SEED,
+    LP_SEED, ORACLE_SEED, STATS_SEED,
 };
 
 #[derive(Accounts)]
@@ -26,12 +29,14 @@ pub struct Swap<'info> {
         mut,
         associated_token::mint = mint_x,
         associated_token::authority = auth,
+        associated_token::token_program = token_program,
     )]
     pub vault_x: Box<InterfaceAccount<'info, TokenAccount>>,
     #[account(
         mut,
         associated_token::mint = mint_y,
         associated_token::authority = auth,
+        associated_token::token_program = token_program,
     )]
     pub vault_y: Box<InterfaceAccount<'info, TokenAccount>>,
     #[account(
@@ -39,6 +44,7 @@ pub struct Swap<'info> {
         payer = user,
         associated_token::mint = mint_x,
         associated_token::authority = user,
+        associated_token::token_program = token_program,
     )]
     pub user_x: Box<InterfaceAccount<'info, TokenAccount>>,
     #[account(
@@ -46,6 +52,7 @@ pub struct Swap<'info> {
         payer = user,
         associated_token::mint = mint_y,
         associated_token::authority = user,
+        associated_token::token_program = token_program,
     )]
     pub user_y: Box<InterfaceAccount<'info, TokenAccount>>,
     #[account(
@@ -53,18 +60,42 @@ pub struct Swap<'info> {
         payer = user,
         associated_token::mint = mint_lp,
         associated_token::authority = user,
+        associated_token::token_program = token_program,
     )]
     pub user_lp: Box<InterfaceAccount<'info, TokenAccount>>,
     /// CHECK: This is safe because it's just used to sign
-    #[account(seeds = [AUTH_SEED.as_ref()], bump = config.auth_bump)]
+    #[account(
+        seeds = [AUTH_SEED.as_ref(), config.key().as_ref()],
+        bump = config.auth_bump
+    )]
     pub auth: UncheckedAccount<'info>,
     #[account(
+        mut,
         has_one = mint_x,
         has_one = mint_y,
         seeds = [CONFIG_SEED.as_ref(), config.seed.to_le_bytes().as_ref()],
         bump = config.config_bump
     )]
     pub config: Box<Account<'info, Config>>,
+    #[account(
+        mut,
+        has_one = config,
+        seeds = [ORACLE_SEED.as_ref(), config.key().as_ref()],
+        bump = oracle.bump
+    )]
+    pub oracle: Box<Account<'info, Oracle>>,
+    #[account(
+        mut,
+        has_one = config,
+        seeds = [STATS_SEED.as_ref(), config.key().as_ref()],
+        bump = stats.bump
+    )]
+    pub stats: Box<Account<'info, Stats>>,
+    #[account(
+        seeds = [ALLOWLIST_SEED.as_ref(), config.key().as_ref(), user.key().as_ref()],
+        bump = allowlist_entry.bump
+    )]
+    pub allowlist_entry: Option<Box<Account<'info, AllowlistEntry>>>,
     pub associated_token_program: Program<'info, AssociatedToken>,
     pub token_program: Interface<'info, TokenInterface>,
     pub system_program: Program<'info, System>,
@@ -73,32 +104,132 @@ pub struct Swap<'info> {
 impl<'info> Swap<'info> {
     pub fn swap(&mut self, is_x: bool, amount: u64, min: u64, expiration: i64) -> Result<()> {
         assert_not_locked!(self.config.locked);
+        assert_allowlisted!(self);
         assert_not_expired!(expiration);
         assert_non_zero!([amount]);
+        self.update_oracle()?;
 
-        let mut curve = ConstantProduct::init(
-            self.vault_x.amount,
-            self.vault_y.amount,
-            self.vault_x.amount,
-            self.config.fee,
-            None,
-        )
-        .map_err(AmmError::from)?;
+        let now = Clock::get()?.unix_timestamp;
+        let pool = self.pool(now);
+        let reserves = pool.reserves()?;
 
-        msg!("curve: {:?}", curve);
+        let (mint_in, mint_out) = self.mints(is_x);
 
-        let p = match is_x {
-            true => LiquidityPair::X,
-            false => LiquidityPair::Y,
-        };
+        // Only what reaches the vault after the transfer fee is priced
+        let amount_in = amount_after_fee(&mint_in, amount)?;
+        assert_non_zero!([amount_in]);
 
-        msg!("p: {:?}", p);
+        let quote = pool.swap(is_x, amount_in)?;
+        assert_non_zero!([quote.amount_in, quote.amount_out]);
 
-        let res = curve.swap(p, amount, min).map_err(AmmError::from)?;
+        // Check for slippage on what the user actually receives
+        let received = amount_after_fee(&mint_out, quote.amount_out)?;
+        require!(received >= min, AmmError::SlippageExceeded);
+
+        let price_move = pool.price_move(is_x, quote.amount_in, quote.amount_out)?;
+        self.config.update_volatility(price_move, now);
+        self.config.accrue_protocol_fee(is_x, quote.fee)?;
+        self.deposit_token(is_x, amount)?;
+        self.withdraw_token(is_x, quote.amount_out)?;
+        self.stats
+            .record_swap(is_x, quote.amount_in, quote.amount_out, quote.fee, now);
+        self.emit_swapped(is_x, quote.amount_in, quote.amount_out, quote.fee, reserves)
+    }
+
+    pub fn swap_exact_out(
+        &mut self,
+        is_x: bool,
+        amount_out: u64, // Exact amount of tokens we withdraw
+        max_in: u64,     // Maximum amount of tokens I'd be willing to deposit
+        expiration: i64,
+    ) -> Result<()> {
+        assert_not_locked!(self.config.locked);
+        assert_allowlisted!(self);
+        assert_not_expired!(expiration);
+        assert_non_zero!([amount_out, max_in]);
+        self.update_oracle()?;
+
+        let now = Clock::get()?.unix_timestamp;
+        let pool = self.pool(now);
+        let reserves = pool.reserves()?;
+
+        let (mint_in, mint_out) = self.mints(is_x);
+
+        // The vault sends enough for `amount_out` to arrive after the transfer fee
+        let gross_out = amount_before_fee(&mint_out, amount_out)?;
+
+        // The input is run back through the curve so fees are accounted the same way as `swap`
+        let quote = pool.swap_exact_out(is_x, gross_out)?;
+        assert_non_zero!([quote.amount_in, quote.amount_out]);
+        let gross_in = amount_before_fee(&mint_in, quote.amount_in)?;
+
+        // Check for slippage
+        require!(gross_in <= max_in, AmmError::SlippageExceeded);
+
+        let price_move = pool.price_move(is_x, quote.amount_in, quote.amount_out)?;
+        self.config.update_volatility(price_move, now);
+        self.config.accrue_protocol_fee(is_x, quote.fee)?;
+        self.deposit_token(is_x, gross_in)?;
+        self.withdraw_token(is_x, gross_out)?;
+        self.stats
+            .record_swap(is_x, quote.amount_in, gross_out, quote.fee, now);
+        self.emit_swapped(is_x, quote.amount_in, gross_out, quote.fee, reserves)
+    }
+
+    pub fn pool(&self, now: i64) -> Pool<'_> {
+        Pool {
+            config: &self.config,
+            vault_x: self.vault_x.amount,
+            vault_y: self.vault_y.amount,
+            lp_supply: self.mint_lp.supply,
+            now,
+        }
+    }
+
+    // Mints of the input and output side
+    pub fn mints(&self, is_x: bool) -> (AccountInfo<'info>, AccountInfo<'info>) {
+        match is_x {
+            true => (self.mint_x.to_account_info(), self.mint_y.to_account_info()),
+            false => (self.mint_y.to_account_info(), self.mint_x.to_account_info()),
+        }
+    }
+
+    // Vault balances are not reloaded after the transfers, so the new reserves are derived here
+    pub fn emit_swapped(
+        &self,
+        is_x: bool,
+        amount_in: u64,
+        amount_out: u64,
+        fee: u64,
+        (reserve_x_before, reserve_y_before): (u64, u64),
+    ) -> Result<()> {
+        let (reserve_x_after, reserve_y_after) = self.config.reserves_after_swap(
+            self.vault_x.amount,
+            self.vault_y.amount,
+            is_x,
+            amount_in,
+            amount_out,
+        )?;
+
+        emit!(Swapped {
+            config: self.config.key(),
+            user: self.user.key(),
+            is_x,
+            amount_in,
+            amount_out,
+            fee,
+            reserve_x_before,
+            reserve_y_before,
+            reserve_x_after,
+            reserve_y_after,
+        });
+        Ok(())
+    }
 
-        assert_non_zero!([res.deposit, res.withdraw]);
-        self.deposit_token(is_x, res.deposit)?;
-        self.withdraw_token(is_x, res.withdraw)?;
+    pub fn update_oracle(&mut self) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let prices = self.pool(now).oracle_prices()?;
+        self.oracle.update(prices, now);
         Ok(())
     }
 
@@ -153,7 +284,12 @@ impl<'info> Swap<'info> {
             mint,
         };
 
-        let seeds = &[&AUTH_SEED.as_ref()[..], &[self.config.auth_bump]];
+        let config_key = self.config.key();
+        let seeds = &[
+            &AUTH_SEED.as_ref()[..],
+            config_key.as_ref(),
+            &[self.config.auth_bump],
+        ];
 
         let signer_seeds = &[&seeds[..]];
 
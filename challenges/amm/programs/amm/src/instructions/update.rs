@@ -0,0 +1,163 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    error::AmmError, has_update_authority, AmpRamped, AuthorityRenounced, AuthorityTransferred,
+    Config, CurveType, DynamicFeeUpdated, FeeUpdated, PoolLocked, PoolUnlocked, ProtocolFeeUpdated,
+    CONFIG_SEED, MAX_AMP, MAX_AMP_CHANGE, MIN_AMP, MIN_RAMP_DURATION,
+};
+
+#[derive(Accounts)]
+pub struct Update<'info> {
+    pub user: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [CONFIG_SEED.as_ref(), config.seed.to_le_bytes().as_ref()],
+        bump = config.config_bump
+    )]
+    pub config: Box<Account<'info, Config>>,
+}
+
+impl<'info> Update<'info> {
+    pub fn lock(&mut self) -> Result<()> {
+        has_update_authority!(self);
+        // The lock doubles as the flash loan guard, so it can't be toggled while one is out
+        require!(self.config.flash_loan == 0, AmmError::FlashLoanNotRepaid);
+        self.config.locked = true;
+        emit!(PoolLocked {
+            config: self.config.key(),
+            authority: self.user.key(),
+        });
+        Ok(())
+    }
+
+    pub fn unlock(&mut self) -> Result<()> {
+        has_update_authority!(self);
+        require!(self.config.flash_loan == 0, AmmError::FlashLoanNotRepaid);
+        self.config.locked = false;
+        emit!(PoolUnlocked {
+            config: self.config.key(),
+            authority: self.user.key(),
+        });
+        Ok(())
+    }
+
+    pub fn update_fee(&mut self, fee: u16) -> Result<()> {
+        has_update_authority!(self);
+        // Don't charge >100.00% as a fee
+        require!(fee <= 10000, AmmError::InvalidFee);
+        let old_fee = self.config.fee;
+        self.config.fee = fee;
+        emit!(FeeUpdated {
+            config: self.config.key(),
+            old_fee,
+            new_fee: fee,
+        });
+        Ok(())
+    }
+
+    pub fn update_protocol_fee(&mut self, protocol_fee: u16) -> Result<()> {
+        has_update_authority!(self);
+        // The protocol can't take more than the whole swap fee
+        require!(protocol_fee <= 10000, AmmError::InvalidFee);
+        let old_protocol_fee = self.config.protocol_fee;
+        self.config.protocol_fee = protocol_fee;
+        emit!(ProtocolFeeUpdated {
+            config: self.config.key(),
+            old_protocol_fee,
+            new_protocol_fee: protocol_fee,
+        });
+        Ok(())
+    }
+
+    pub fn enable_dynamic_fee(&mut self, min_fee: u16, max_fee: u16) -> Result<()> {
+        has_update_authority!(self);
+        require!(min_fee <= max_fee && max_fee <= 10000, AmmError::InvalidFee);
+        self.config.dynamic_fee = true;
+        self.config.min_fee = min_fee;
+        self.config.max_fee = max_fee;
+        // Start calm rather than carry over moves from before the fee was dynamic
+        self.config.volatility = 0;
+        self.config.volatility_updated = Clock::get()?.unix_timestamp;
+        emit!(DynamicFeeUpdated {
+            config: self.config.key(),
+            enabled: true,
+            min_fee,
+            max_fee,
+        });
+        Ok(())
+    }
+
+    // Back to the fixed `fee`
+    pub fn disable_dynamic_fee(&mut self) -> Result<()> {
+        has_update_authority!(self);
+        self.config.dynamic_fee = false;
+        self.config.volatility = 0;
+        emit!(DynamicFeeUpdated {
+            config: self.config.key(),
+            enabled: false,
+            min_fee: self.config.min_fee,
+            max_fee: self.config.max_fee,
+        });
+        Ok(())
+    }
+
+    pub fn ramp_amp(&mut self, target_amp: u64, ramp_end: i64) -> Result<()> {
+        has_update_authority!(self);
+        require!(
+            self.config.curve == CurveType::StableSwap,
+            AmmError::UnsupportedCurve
+        );
+        require!(
+            (MIN_AMP..=MAX_AMP).contains(&target_amp),
+            AmmError::InvalidAmplification
+        );
+
+        let now = Clock::get()?.unix_timestamp;
+        require!(
+            ramp_end >= now.saturating_add(MIN_RAMP_DURATION),
+            AmmError::InvalidRamp
+        );
+
+        // Start from wherever an ongoing ramp has got to, and don't move too far in one go
+        let initial_amp = self.config.amp(now);
+        require!(
+            target_amp <= initial_amp * MAX_AMP_CHANGE
+                && target_amp * MAX_AMP_CHANGE >= initial_amp,
+            AmmError::InvalidRamp
+        );
+
+        self.config.initial_amp = initial_amp;
+        self.config.target_amp = target_amp;
+        self.config.ramp_start = now;
+        self.config.ramp_end = ramp_end;
+        emit!(AmpRamped {
+            config: self.config.key(),
+            initial_amp,
+            target_amp,
+            ramp_start: now,
+            ramp_end,
+        });
+        Ok(())
+    }
+
+    pub fn transfer_authority(&mut self, new_authority: Pubkey) -> Result<()> {
+        has_update_authority!(self);
+        self.config.authority = Some(new_authority);
+        emit!(AuthorityTransferred {
+            config: self.config.key(),
+            old_authority: self.user.key(),
+            new_authority,
+        });
+        Ok(())
+    }
+
+    pub fn renounce_authority(&mut self) -> Result<()> {
+        has_update_authority!(self);
+        self.config.authority = None;
+        emit!(AuthorityRenounced {
+            config: self.config.key(),
+            old_authority: self.user.key(),
+        });
+        Ok(())
+    }
+}
@@ -0,0 +1,164 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{
+        burn, transfer_checked, Burn, Mint, TokenAccount, TokenInterface, TransferChecked,
+    },
+};
+
+use crate::{
+    assert_non_zero, assert_not_expired, assert_not_locked, error::AmmError,
+    helpers::amount_after_fee, quote::Pool, Config, AUTH_SEED, CONFIG_SEED, LP_SEED,
+};
+
+#[derive(Accounts)]
+pub struct Withdraw<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+    pub mint_x: Box<InterfaceAccount<'info, Mint>>,
+    pub mint_y: Box<InterfaceAccount<'info, Mint>>,
+    #[account(
+        mut,
+        seeds = [LP_SEED.as_ref(), config.key().as_ref()],
+        bump = config.lp_bump
+    )]
+    pub mint_lp: Box<InterfaceAccount<'info, Mint>>,
+    #[account(
+        mut,
+        associated_token::mint = mint_x,
+        associated_token::authority = auth,
+        associated_token::token_program = token_program,
+    )]
+    pub vault_x: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        mut,
+        associated_token::mint = mint_y,
+        associated_token::authority = auth,
+        associated_token::token_program = token_program,
+    )]
+    pub vault_y: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        init_if_needed,
+        payer = user,
+        associated_token::mint = mint_x,
+        associated_token::authority = user,
+        associated_token::token_program = token_program,
+    )]
+    pub user_x: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        init_if_needed,
+        payer = user,
+        associated_token::mint = mint_y,
+        associated_token::authority = user,
+        associated_token::token_program = token_program,
+    )]
+    pub user_y: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        mut,
+        associated_token::mint = mint_lp,
+        associated_token::authority = user,
+        associated_token::token_program = token_program,
+    )]
+    pub user_lp: Box<InterfaceAccount<'info, TokenAccount>>,
+    /// CHECK: This is safe because it's just used to sign
+    #[account(
+        seeds = [AUTH_SEED.as_ref(), config.key().as_ref()],
+        bump = config.auth_bump
+    )]
+    pub auth: UncheckedAccount<'info>,
+    #[account(
+        has_one = mint_x,
+        has_one = mint_y,
+        seeds = [CONFIG_SEED.as_ref(), config.seed.to_le_bytes().as_ref()],
+        bump = config.config_bump
+    )]
+    pub config: Box<Account<'info, Config>>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> Withdraw<'info> {
+    pub fn withdraw(
+        &self,
+        amount: u64, // Amount of LP token to burn
+        min_x: u64,  // Min amount of X we are willing to withdraw
+        min_y: u64,  // Min amount of Y we are willing to withdraw
+        expiration: i64,
+    ) -> Result<()> {
+        assert_not_locked!(self.config.locked);
+        assert_not_expired!(expiration);
+        assert_non_zero!([amount]);
+        require!(amount <= self.user_lp.amount, AmmError::InsufficientBalance);
+
+        let quote = Pool {
+            config: &self.config,
+            vault_x: self.vault_x.amount,
+            vault_y: self.vault_y.amount,
+            lp_supply: self.mint_lp.supply,
+            now: Clock::get()?.unix_timestamp,
+        }
+        .withdraw(amount)?;
+
+        // Check for slippage on what the user actually receives
+        let received_x = amount_after_fee(&self.mint_x.to_account_info(), quote.amount_x)?;
+        let received_y = amount_after_fee(&self.mint_y.to_account_info(), quote.amount_y)?;
+        require!(
+            received_x >= min_x && received_y >= min_y,
+            AmmError::SlippageExceeded
+        );
+
+        self.withdraw_tokens(true, quote.amount_x)?;
+        self.withdraw_tokens(false, quote.amount_y)?;
+        self.burn_lp_tokens(amount)
+    }
+
+    pub fn withdraw_tokens(&self, is_x: bool, amount: u64) -> Result<()> {
+        let (from, to, mint, decimals) = match is_x {
+            true => (
+                self.vault_x.to_account_info(),
+                self.user_x.to_account_info(),
+                self.mint_x.to_account_info(),
+                self.mint_x.decimals,
+            ),
+            false => (
+                self.vault_y.to_account_info(),
+                self.user_y.to_account_info(),
+                self.mint_y.to_account_info(),
+                self.mint_y.decimals,
+            ),
+        };
+
+        let cpi_account = TransferChecked {
+            from,
+            to,
+            authority: self.auth.to_account_info(),
+            mint,
+        };
+
+        let config_key = self.config.key();
+        let seeds = &[
+            &AUTH_SEED.as_ref()[..],
+            config_key.as_ref(),
+            &[self.config.auth_bump],
+        ];
+        let signer_seeds = &[&seeds[..]];
+        let cpi_context = CpiContext::new_with_signer(
+            self.token_program.to_account_info(),
+            cpi_account,
+            signer_seeds,
+        );
+        transfer_checked(cpi_context, amount, decimals)
+    }
+
+    pub fn burn_lp_tokens(&self, amount: u64) -> Result<()> {
+        let cpi_account = Burn {
+            mint: self.mint_lp.to_account_info(),
+            from: self.user_lp.to_account_info(),
+            authority: self.user.to_account_info(),
+        };
+
+        let cpi_context = CpiContext::new(self.token_program.to_account_info(), cpi_account);
+        burn(cpi_context, amount)
+    }
+}
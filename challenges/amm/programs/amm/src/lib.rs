@@ -1,12 +1,17 @@
+pub mod clmm;
 pub mod constants;
 pub mod error;
+pub mod events;
 pub mod helpers;
 pub mod instructions;
+pub mod quote;
 pub mod state;
 
 use anchor_lang::prelude::*;
 
+pub use clmm::*;
 pub use constants::*;
+pub use events::*;
 pub use instructions::*;
 pub use state::*;
 
@@ -21,8 +26,42 @@ pub mod amm {
         seed: u64,
         fee: u16,
         authority: Option<Pubkey>,
+        curve: CurveType,
+        amp: u64,
+        lp_decimals: u8,
+        permissioned: bool,
     ) -> Result<()> {
-        initialize::handler(ctx, seed, fee, authority)
+        initialize::handler(
+            ctx,
+            seed,
+            fee,
+            authority,
+            curve,
+            amp,
+            lp_decimals,
+            permissioned,
+        )
+    }
+
+    pub fn add_to_allowlist(ctx: Context<AddToAllowlist>, wallet: Pubkey) -> Result<()> {
+        ctx.accounts
+            .add_to_allowlist(wallet, ctx.bumps.allowlist_entry)
+    }
+
+    pub fn remove_from_allowlist(ctx: Context<RemoveFromAllowlist>) -> Result<()> {
+        ctx.accounts.remove_from_allowlist()
+    }
+
+    pub fn register_pair(ctx: Context<RegisterPair>) -> Result<()> {
+        ctx.accounts.register_pair(ctx.bumps.pair)
+    }
+
+    pub fn contest_pair(ctx: Context<ContestPair>) -> Result<()> {
+        ctx.accounts.contest_pair()
+    }
+
+    pub fn create_lp_metadata(ctx: Context<CreateLpMetadata>) -> Result<()> {
+        ctx.accounts.create_lp_metadata()
     }
 
     pub fn deposit(
@@ -35,6 +74,27 @@ pub mod amm {
         ctx.accounts.deposit(amount, max_x, max_y, expiration)
     }
 
+    pub fn deposit_single(
+        ctx: Context<Deposit>,
+        is_x: bool,
+        amount_in: u64,
+        min_lp: u64,
+        expiration: i64,
+    ) -> Result<()> {
+        ctx.accounts
+            .deposit_single(is_x, amount_in, min_lp, expiration)
+    }
+
+    pub fn withdraw(
+        ctx: Context<Withdraw>,
+        amount: u64,
+        min_x: u64,
+        min_y: u64,
+        expiration: i64,
+    ) -> Result<()> {
+        ctx.accounts.withdraw(amount, min_x, min_y, expiration)
+    }
+
     pub fn swap(
         ctx: Context<Swap>,
         is_x: bool,
@@ -42,11 +102,171 @@ pub mod amm {
         min: u64,    // Minimum amount of tokens I'd be willing to withdraw
         expiration: i64,
     ) -> Result<()> {
-        // Swap Token X for Token Y or vice versa
-        msg!("Swap Token X for Token Y or vice versa");
-        msg!("is_x: {}", is_x);
-        msg!("amount: {}", amount);
-        msg!("min: {}", min);
         ctx.accounts.swap(is_x, amount, min, expiration)
     }
+
+    pub fn swap_exact_out(
+        ctx: Context<Swap>,
+        is_x: bool,
+        amount_out: u64, // Exact amount of tokens we withdraw
+        max_in: u64,     // Maximum amount of tokens I'd be willing to deposit
+        expiration: i64,
+    ) -> Result<()> {
+        ctx.accounts
+            .swap_exact_out(is_x, amount_out, max_in, expiration)
+    }
+
+    pub fn route_swap<'info>(
+        ctx: Context<'_, '_, 'info, 'info, RouteSwap<'info>>,
+        hops: u8,
+        amount_in: u64,
+        min_out: u64,
+        expiration: i64,
+    ) -> Result<()> {
+        ctx.accounts.route_swap(
+            ctx.remaining_accounts,
+            ctx.program_id,
+            hops,
+            amount_in,
+            min_out,
+            expiration,
+        )
+    }
+
+    pub fn flash_loan(ctx: Context<FlashLoan>, is_x: bool, amount: u64) -> Result<()> {
+        ctx.accounts.flash_loan(is_x, amount)
+    }
+
+    pub fn flash_repay(ctx: Context<FlashLoan>) -> Result<()> {
+        ctx.accounts.flash_repay()
+    }
+
+    pub fn init_farm(ctx: Context<InitFarm>) -> Result<()> {
+        ctx.accounts.init_farm(ctx.bumps.farm)
+    }
+
+    pub fn fund_farm(ctx: Context<FundFarm>, amount: u64, reward_rate: u64) -> Result<()> {
+        ctx.accounts.fund(amount, reward_rate)
+    }
+
+    pub fn stake_lp(ctx: Context<Stake>, amount: u64) -> Result<()> {
+        ctx.accounts.stake_lp(amount, ctx.bumps.position)
+    }
+
+    pub fn unstake_lp(ctx: Context<Stake>, amount: u64) -> Result<()> {
+        ctx.accounts.unstake_lp(amount, ctx.bumps.position)
+    }
+
+    pub fn claim_rewards(ctx: Context<Stake>) -> Result<()> {
+        ctx.accounts.claim_rewards(ctx.bumps.position)
+    }
+
+    pub fn observe(ctx: Context<Observe>, window: u32) -> Result<Twap> {
+        ctx.accounts.observe(window)
+    }
+
+    pub fn initialize_cl_pool(
+        ctx: Context<InitializeClPool>,
+        tick_spacing: u16,
+        fee: u16,
+        sqrt_price: u128,
+    ) -> Result<()> {
+        ctx.accounts
+            .initialize_cl_pool(tick_spacing, fee, sqrt_price, ctx.bumps.pool)
+    }
+
+    pub fn open_position(ctx: Context<OpenPosition>, lower: i32, upper: i32) -> Result<()> {
+        ctx.accounts.open_position(lower, upper, &ctx.bumps)
+    }
+
+    pub fn increase_liquidity(
+        ctx: Context<ModifyPosition>,
+        liquidity: u128,
+        max_x: u64,
+        max_y: u64,
+        expiration: i64,
+    ) -> Result<()> {
+        ctx.accounts
+            .increase_liquidity(liquidity, max_x, max_y, expiration)
+    }
+
+    pub fn decrease_liquidity(
+        ctx: Context<ModifyPosition>,
+        liquidity: u128,
+        min_x: u64,
+        min_y: u64,
+        expiration: i64,
+    ) -> Result<()> {
+        ctx.accounts
+            .decrease_liquidity(liquidity, min_x, min_y, expiration)
+    }
+
+    pub fn collect_fees(ctx: Context<ModifyPosition>) -> Result<()> {
+        ctx.accounts.collect_fees()
+    }
+
+    pub fn close_position(ctx: Context<ClosePosition>) -> Result<()> {
+        ctx.accounts.close_position()
+    }
+
+    pub fn cl_swap<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ClSwap<'info>>,
+        is_x: bool,
+        amount: u64,
+        min: u64,
+        expiration: i64,
+    ) -> Result<()> {
+        ctx.accounts.cl_swap(
+            ctx.remaining_accounts,
+            ctx.program_id,
+            is_x,
+            amount,
+            min,
+            expiration,
+        )
+    }
+
+    pub fn lock(ctx: Context<Update>) -> Result<()> {
+        ctx.accounts.lock()
+    }
+
+    pub fn unlock(ctx: Context<Update>) -> Result<()> {
+        ctx.accounts.unlock()
+    }
+
+    pub fn update_fee(ctx: Context<Update>, fee: u16) -> Result<()> {
+        ctx.accounts.update_fee(fee)
+    }
+
+    pub fn update_protocol_fee(ctx: Context<Update>, protocol_fee: u16) -> Result<()> {
+        ctx.accounts.update_protocol_fee(protocol_fee)
+    }
+
+    pub fn enable_dynamic_fee(ctx: Context<Update>, min_fee: u16, max_fee: u16) -> Result<()> {
+        ctx.accounts.enable_dynamic_fee(min_fee, max_fee)
+    }
+
+    pub fn disable_dynamic_fee(ctx: Context<Update>) -> Result<()> {
+        ctx.accounts.disable_dynamic_fee()
+    }
+
+    pub fn collect_protocol_fees(ctx: Context<CollectProtocolFees>) -> Result<()> {
+        ctx.accounts.collect()
+    }
+
+    pub fn close_pool(ctx: Context<ClosePool>) -> Result<()> {
+        ctx.accounts.close_pool()
+    }
+
+    pub fn ramp_amp(ctx: Context<Update>, target_amp: u64, ramp_end: i64) -> Result<()> {
+        ctx.accounts.ramp_amp(target_amp, ramp_end)
+    }
+
+    pub fn transfer_authority(ctx: Context<Update>, new_authority: Pubkey) -> Result<()> {
+        ctx.accounts.transfer_authority(new_authority)
+    }
+
+    pub fn renounce_authority(ctx: Context<Update>) -> Result<()> {
+        ctx.accounts.renounce_authority()
+    }
 }
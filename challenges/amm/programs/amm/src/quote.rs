@@ -0,0 +1,227 @@
+use anchor_lang::prelude::*;
+use constant_product_curve::ConstantProduct;
+
+use crate::{
+    error::AmmError,
+    helpers::{mul_div, stable_invariant},
+    Config, CurveType, MINIMUM_LIQUIDITY,
+};
+
+// Q64.64 fixed point one
+const ONE: u128 = 1 << 64;
+
+// A pool as read from its accounts: the deserialized `Config`, the vault balances and the
+// LP supply. The instructions price through the same methods, so a quote matches what the
+// program does with the amounts that reach the vaults. Token-2022 transfer fees are not
+// included and have to be taken off the amounts by the caller.
+pub struct Pool<'a> {
+    pub config: &'a Config,
+    pub vault_x: u64,
+    pub vault_y: u64,
+    pub lp_supply: u64,
+    pub now: i64, // Unix timestamp, used to follow an amplification ramp
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SwapQuote {
+    pub amount_in: u64,
+    pub amount_out: u64,
+    pub fee: u64, // Swap fee taken from the input, protocol share included
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DepositQuote {
+    pub amount_x: u64,
+    pub amount_y: u64,
+    pub lp_amount: u64,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct WithdrawQuote {
+    pub amount_x: u64,
+    pub amount_y: u64,
+}
+
+impl<'a> Pool<'a> {
+    pub fn reserves(&self) -> Result<(u64, u64)> {
+        self.config.reserves(self.vault_x, self.vault_y)
+    }
+
+    // Output of `swap` for `amount_in` of X (or Y)
+    pub fn swap(&self, is_x: bool, amount_in: u64) -> Result<SwapQuote> {
+        let reserves = self.reserves()?;
+        let res = self
+            .config
+            .swap(reserves, self.lp_supply, is_x, amount_in, 0, self.now)?;
+
+        Ok(SwapQuote {
+            amount_in: res.deposit,
+            amount_out: res.withdraw,
+            fee: res.fee,
+        })
+    }
+
+    // Input `swap_exact_out` charges for exactly `amount_out` of Y (or X)
+    pub fn swap_exact_out(&self, is_x: bool, amount_out: u64) -> Result<SwapQuote> {
+        let reserves = self.reserves()?;
+        let amount_in = self
+            .config
+            .amount_in_for_exact_out(reserves, is_x, amount_out, self.now)?;
+        let res = self.config.swap(
+            reserves,
+            self.lp_supply,
+            is_x,
+            amount_in,
+            amount_out,
+            self.now,
+        )?;
+
+        Ok(SwapQuote {
+            amount_in: res.deposit,
+            amount_out,
+            fee: res.fee,
+        })
+    }
+
+    // Tokens `deposit` takes for `lp_amount`. The first deposit takes all of `max_x` and
+    // `max_y` instead and `lp_amount` is what it mints, after locking the minimum liquidity.
+    // Anything donated to the vaults before then counts towards the initial liquidity
+    pub fn deposit(&self, lp_amount: u64, max_x: u64, max_y: u64) -> Result<DepositQuote> {
+        let (x, y) = self.reserves()?;
+
+        if self.lp_supply == 0 {
+            let liquidity = self.config.initial_liquidity(
+                x.checked_add(max_x).ok_or(AmmError::Overflow)?,
+                y.checked_add(max_y).ok_or(AmmError::Overflow)?,
+                self.now,
+            )?;
+            require!(
+                liquidity > MINIMUM_LIQUIDITY,
+                AmmError::LiquidityLessThanMinimum
+            );
+            return Ok(DepositQuote {
+                amount_x: max_x,
+                amount_y: max_y,
+                lp_amount: liquidity - MINIMUM_LIQUIDITY,
+            });
+        }
+
+        let amounts = ConstantProduct::xy_deposit_amounts_from_l(
+            x,
+            y,
+            self.lp_supply,
+            lp_amount,
+            self.config.lp_decimals as u32,
+        )
+        .map_err(AmmError::from)?;
+        Ok(DepositQuote {
+            amount_x: amounts.x,
+            amount_y: amounts.y,
+            lp_amount,
+        })
+    }
+
+    // Tokens `withdraw` pays out for burning `lp_amount`
+    pub fn withdraw(&self, lp_amount: u64) -> Result<WithdrawQuote> {
+        let (x, y) = self.reserves()?;
+        let amounts = ConstantProduct::xy_withdraw_amounts_from_l(
+            x,
+            y,
+            self.lp_supply,
+            lp_amount,
+            self.config.lp_decimals as u32,
+        )
+        .map_err(AmmError::from)?;
+        Ok(WithdrawQuote {
+            amount_x: amounts.x,
+            amount_y: amounts.y,
+        })
+    }
+
+    // Marginal price of X in Y (or Y in X) as Q64.64, before fees
+    pub fn spot_price(&self, is_x: bool) -> Result<u128> {
+        self.spot_price_at(self.reserves()?, is_x)
+    }
+
+    // Basis points the spot price of X moves by when `amount_in` is swapped for `amount_out`,
+    // only tracked for the dynamic fee so it's zero when that is off
+    pub fn price_move(&self, is_x: bool, amount_in: u64, amount_out: u64) -> Result<u64> {
+        if !self.config.dynamic_fee {
+            return Ok(0);
+        }
+        let (x, y) = self.reserves()?;
+        let after = match is_x {
+            true => (
+                x.checked_add(amount_in).ok_or(AmmError::Overflow)?,
+                y.checked_sub(amount_out).ok_or(AmmError::Underflow)?,
+            ),
+            false => (
+                x.checked_sub(amount_out).ok_or(AmmError::Underflow)?,
+                y.checked_add(amount_in).ok_or(AmmError::Overflow)?,
+            ),
+        };
+        // Emptying a side is as volatile as it gets
+        if after.0 == 0 || after.1 == 0 {
+            return Ok(10000);
+        }
+
+        let before = self.spot_price_at((x, y), true)?;
+        let after = self.spot_price_at(after, true)?;
+        let price_move =
+            mul_div(before.abs_diff(after), 10000, before).ok_or(AmmError::Overflow)?;
+        Ok(u64::try_from(price_move).unwrap_or(u64::MAX))
+    }
+
+    // Spot prices of X in Y and of Y in X for the oracle, none while either side is empty
+    pub fn oracle_prices(&self) -> Result<Option<(u128, u128)>> {
+        let reserves = self.reserves()?;
+        if reserves.0 == 0 || reserves.1 == 0 {
+            return Ok(None);
+        }
+        Ok(Some((
+            self.spot_price_at(reserves, true)?,
+            self.spot_price_at(reserves, false)?,
+        )))
+    }
+
+    fn spot_price_at(&self, (x, y): (u64, u64), is_x: bool) -> Result<u128> {
+        require!(x > 0 && y > 0, AmmError::NoLiquidityInPool);
+
+        let (numerator, denominator) = match self.config.curve {
+            CurveType::ConstantProduct => (y as u128, x as u128),
+            CurveType::StableSwap => {
+                // -dy/dx of the invariant, scaled by 4xy / D:
+                // (16A * xy / D + D^2 / x) / (16A * xy / D + D^2 / y)
+                let amp = self.config.amp(self.now);
+                let d = stable_invariant(x, y, amp)?;
+                let t = mul_div(amp as u128 * 16, x as u128 * y as u128, d)
+                    .ok_or(AmmError::Overflow)?;
+                let d2_over_x = mul_div(d, d, x as u128).ok_or(AmmError::Overflow)?;
+                let d2_over_y = mul_div(d, d, y as u128).ok_or(AmmError::Overflow)?;
+                (
+                    t.checked_add(d2_over_x).ok_or(AmmError::Overflow)?,
+                    t.checked_add(d2_over_y).ok_or(AmmError::Overflow)?,
+                )
+            }
+        };
+
+        let (numerator, denominator) = match is_x {
+            true => (numerator, denominator),
+            false => (denominator, numerator),
+        };
+        mul_div(numerator, ONE, denominator).ok_or(AmmError::Overflow.into())
+    }
+
+    // Basis points the output of `quote` falls short of the spot price, fee excluded. Only
+    // meant for off-chain callers, the program never prices through it
+    pub fn price_impact(&self, is_x: bool, quote: &SwapQuote) -> Result<u64> {
+        let spot = self.spot_price(is_x)?;
+        let net_in = quote.amount_in.saturating_sub(quote.fee);
+        let ideal = mul_div(net_in as u128, spot, ONE).ok_or(AmmError::Overflow)?;
+        let amount_out = quote.amount_out;
+        if ideal == 0 || amount_out as u128 >= ideal {
+            return Ok(0);
+        }
+        Ok(((ideal - amount_out as u128) * 10000 / ideal) as u64)
+    }
+}
@@ -0,0 +1,20 @@
+use crate::constants::*;
+use anchor_lang::prelude::*;
+
+// A wallet allowed to trade on a permissioned pool, at [ALLOWLIST_SEED, config, wallet]
+#[account]
+pub struct AllowlistEntry {
+    pub config: Pubkey,
+    pub wallet: Pubkey,
+    pub bump: u8,
+}
+
+impl AllowlistEntry {
+    pub const LEN: usize = DISCRIMINATOR_SIZE + (PUBKEY_SIZE * 2) + U8_SIZE;
+
+    pub fn init(&mut self, config: Pubkey, wallet: Pubkey, bump: u8) {
+        self.config = config;
+        self.wallet = wallet;
+        self.bump = bump;
+    }
+}
@@ -1,13 +1,44 @@
-use crate::constants::*;
+use crate::{
+    constants::*,
+    error::AmmError,
+    helpers::{
+        amount_in_for_exact_out, sqrt_u128, stable_amount_in_for_exact_out, stable_invariant,
+        stable_swap_out,
+    },
+};
 use anchor_lang::prelude::*;
+use constant_product_curve::{ConstantProduct, LiquidityPair, SwapResult};
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum CurveType {
+    ConstantProduct,
+    StableSwap,
+}
 
 #[account]
 pub struct Config {
     pub seed: u64,
     pub authority: Option<Pubkey>,
-    pub mint_x: Pubkey, // Token X Mint
-    pub mint_y: Pubkey, // Token Y Mint
-    pub fee: u16,       // Swap fee in basis points
+    pub permissioned: bool,
+    pub mint_x: Pubkey,       // Token X Mint
+    pub mint_y: Pubkey,       // Token Y Mint
+    pub fee: u16,             // Swap fee in basis points
+    pub protocol_fee: u16,    // Protocol share of the swap fee in basis points
+    pub protocol_fees_x: u64, // Accrued protocol fees in token X
+    pub protocol_fees_y: u64, // Accrued protocol fees in token Y
+    pub lp_decimals: u8,      // Decimals of mint_lp, also the precision of the LP curve math
+    pub curve: CurveType,
+    pub initial_amp: u64, // StableSwap amplification at the start of the ramp
+    pub target_amp: u64,  // StableSwap amplification at the end of the ramp
+    pub ramp_start: i64,
+    pub ramp_end: i64,
+    pub dynamic_fee: bool, // Swap fee follows recent volatility between min_fee and max_fee
+    pub min_fee: u16,      // Dynamic fee bounds in basis points
+    pub max_fee: u16,
+    pub volatility: u64, // Recent moves of the spot price in basis points, fading over time
+    pub volatility_updated: i64,
+    pub flash_loan: u64, // Outstanding flash loan, repaid within the same transaction
+    pub flash_loan_is_x: bool, // Token the flash loan was taken in
     pub locked: bool,
     pub auth_bump: u8,
     pub config_bump: u8,
@@ -18,8 +49,20 @@ impl Config {
     pub const LEN: usize = DISCRIMINATOR_SIZE
         + U64_SIZE
         + OPTION_SIZE
+        + BOOL_SIZE
         + (PUBKEY_SIZE * 3)
-        + U16_SIZE
+        + (U16_SIZE * 2)
+        + (U64_SIZE * 2)
+        + U8_SIZE
+        + U8_SIZE
+        + (U64_SIZE * 2)
+        + (I64_SIZE * 2)
+        + BOOL_SIZE
+        + (U16_SIZE * 2)
+        + U64_SIZE
+        + I64_SIZE
+        + U64_SIZE
+        + BOOL_SIZE
         + BOOL_SIZE
         + (U8_SIZE * 3);
 
@@ -27,21 +70,224 @@ impl Config {
         &mut self,
         seed: u64,
         authority: Option<Pubkey>,
+        permissioned: bool,
         mint_x: Pubkey,
         mint_y: Pubkey,
         fee: u16,
+        lp_decimals: u8,
         auth_bump: u8,
         config_bump: u8,
         lp_bump: u8,
     ) {
         self.seed = seed;
         self.authority = authority;
+        self.permissioned = permissioned;
         self.mint_x = mint_x;
         self.mint_y = mint_y;
         self.fee = fee;
+        self.protocol_fee = 0;
+        self.protocol_fees_x = 0;
+        self.protocol_fees_y = 0;
+        self.lp_decimals = lp_decimals;
+        self.dynamic_fee = false;
+        self.min_fee = 0;
+        self.max_fee = 0;
+        self.volatility = 0;
+        self.volatility_updated = 0;
+        self.flash_loan = 0;
+        self.flash_loan_is_x = false;
         self.locked = false;
         self.auth_bump = auth_bump;
         self.config_bump = config_bump;
         self.lp_bump = lp_bump;
     }
+
+    pub fn init_curve(&mut self, curve: CurveType, amp: u64, now: i64) -> Result<()> {
+        match curve {
+            CurveType::ConstantProduct => require!(amp == 0, AmmError::InvalidAmplification),
+            CurveType::StableSwap => require!(
+                (MIN_AMP..=MAX_AMP).contains(&amp),
+                AmmError::InvalidAmplification
+            ),
+        }
+        self.curve = curve;
+        self.initial_amp = amp;
+        self.target_amp = amp;
+        self.ramp_start = now;
+        self.ramp_end = now;
+        Ok(())
+    }
+
+    // Amplification coefficient, moving linearly from `initial_amp` to `target_amp` during a ramp
+    pub fn amp(&self, now: i64) -> u64 {
+        if now >= self.ramp_end {
+            return self.target_amp;
+        }
+
+        let elapsed = (now.max(self.ramp_start) - self.ramp_start) as u128;
+        let duration = (self.ramp_end - self.ramp_start) as u128;
+        let (initial, target) = (self.initial_amp as u128, self.target_amp as u128);
+        let amp = match target > initial {
+            true => initial + (target - initial) * elapsed / duration,
+            false => initial - (initial - target) * elapsed / duration,
+        };
+        amp as u64
+    }
+
+    // Volatility left at `now`, fading linearly to zero over VOLATILITY_DECAY seconds
+    pub fn volatility(&self, now: i64) -> u64 {
+        let elapsed = now
+            .saturating_sub(self.volatility_updated)
+            .clamp(0, VOLATILITY_DECAY);
+        (self.volatility as u128 * (VOLATILITY_DECAY - elapsed) as u128 / VOLATILITY_DECAY as u128)
+            as u64
+    }
+
+    // Swap fee charged at `now`. With the dynamic fee on, every VOLATILITY_FEE_DIVISOR basis
+    // points of recent price movement add one to `min_fee`, up to `max_fee`
+    pub fn effective_fee(&self, now: i64) -> u16 {
+        if !self.dynamic_fee {
+            return self.fee;
+        }
+        let fee = self.min_fee as u64 + self.volatility(now) / VOLATILITY_FEE_DIVISOR;
+        fee.min(self.max_fee as u64) as u16
+    }
+
+    // Adds the price move of a swap, in basis points, to what is left of the volatility
+    pub fn update_volatility(&mut self, price_move: u64, now: i64) {
+        if !self.dynamic_fee {
+            return;
+        }
+        self.volatility = self.volatility(now).saturating_add(price_move);
+        self.volatility_updated = now;
+    }
+
+    // LP supply minted by the first deposit
+    pub fn initial_liquidity(&self, x: u64, y: u64, now: i64) -> Result<u64> {
+        let liquidity = match self.curve {
+            CurveType::ConstantProduct => sqrt_u128(x as u128 * y as u128),
+            CurveType::StableSwap => stable_invariant(x, y, self.amp(now))?,
+        };
+        u64::try_from(liquidity).map_err(|_| AmmError::Overflow.into())
+    }
+
+    // Swap `amount` against the reserves on the pool's curve
+    pub fn swap(
+        &self,
+        (x, y): (u64, u64),
+        supply: u64,
+        is_x: bool,
+        amount: u64,
+        min: u64,
+        now: i64,
+    ) -> Result<SwapResult> {
+        match self.curve {
+            CurveType::ConstantProduct => {
+                let p = match is_x {
+                    true => LiquidityPair::X,
+                    false => LiquidityPair::Y,
+                };
+                let res = ConstantProduct::init(x, y, supply, self.effective_fee(now), None)
+                    .map_err(AmmError::from)?
+                    .swap(p, amount, min)
+                    .map_err(AmmError::from)?;
+                Ok(res)
+            }
+            CurveType::StableSwap => {
+                let (reserve_in, reserve_out) = match is_x {
+                    true => (x, y),
+                    false => (y, x),
+                };
+                let (withdraw, fee) = stable_swap_out(
+                    reserve_in,
+                    reserve_out,
+                    amount,
+                    self.amp(now),
+                    self.effective_fee(now),
+                )?;
+                require!(withdraw >= min, AmmError::SlippageExceeded);
+                Ok(SwapResult {
+                    deposit: amount,
+                    withdraw,
+                    fee,
+                })
+            }
+        }
+    }
+
+    // Smallest input that pays out `amount_out` on the pool's curve
+    pub fn amount_in_for_exact_out(
+        &self,
+        (x, y): (u64, u64),
+        is_x: bool,
+        amount_out: u64,
+        now: i64,
+    ) -> Result<u64> {
+        let (reserve_in, reserve_out) = match is_x {
+            true => (x, y),
+            false => (y, x),
+        };
+        match self.curve {
+            CurveType::ConstantProduct => amount_in_for_exact_out(
+                reserve_in,
+                reserve_out,
+                amount_out,
+                self.effective_fee(now),
+            ),
+            CurveType::StableSwap => stable_amount_in_for_exact_out(
+                reserve_in,
+                reserve_out,
+                amount_out,
+                self.amp(now),
+                self.effective_fee(now),
+            ),
+        }
+    }
+    // Vault balances minus the protocol fees that have not been collected yet
+    pub fn reserves(&self, vault_x: u64, vault_y: u64) -> Result<(u64, u64)> {
+        let x = vault_x
+            .checked_sub(self.protocol_fees_x)
+            .ok_or(AmmError::Underflow)?;
+        let y = vault_y
+            .checked_sub(self.protocol_fees_y)
+            .ok_or(AmmError::Underflow)?;
+        Ok((x, y))
+    }
+
+    // Reserves once a swap of `amount_in` for `amount_out` has settled in the vaults
+    pub fn reserves_after_swap(
+        &self,
+        vault_x: u64,
+        vault_y: u64,
+        is_x: bool,
+        amount_in: u64,
+        amount_out: u64,
+    ) -> Result<(u64, u64)> {
+        let (vault_x, vault_y) = match is_x {
+            true => (
+                vault_x.checked_add(amount_in).ok_or(AmmError::Overflow)?,
+                vault_y.checked_sub(amount_out).ok_or(AmmError::Underflow)?,
+            ),
+            false => (
+                vault_x.checked_sub(amount_out).ok_or(AmmError::Underflow)?,
+                vault_y.checked_add(amount_in).ok_or(AmmError::Overflow)?,
+            ),
+        };
+        self.reserves(vault_x, vault_y)
+    }
+
+    pub fn accrue_protocol_fee(&mut self, is_x: bool, fee: u64) -> Result<()> {
+        let amount = (fee as u128)
+            .checked_mul(self.protocol_fee as u128)
+            .ok_or(AmmError::Overflow)?
+            / 10000;
+        let counter = match is_x {
+            true => &mut self.protocol_fees_x,
+            false => &mut self.protocol_fees_y,
+        };
+        *counter = counter
+            .checked_add(amount as u64)
+            .ok_or(AmmError::Overflow)?;
+        Ok(())
+    }
 }
@@ -0,0 +1,86 @@
+use crate::{constants::*, error::AmmError, helpers::mul_div};
+use anchor_lang::prelude::*;
+
+#[account]
+pub struct Farm {
+    pub config: Pubkey,
+    pub reward_mint: Pubkey,
+    pub reward_rate: u64, // Reward tokens emitted per second across all stakers
+    pub reward_remaining: u64, // Funded rewards that have not been emitted yet
+    pub reward_per_share: u128, // Rewards per staked LP token as Q64.64, only ever grows
+    pub total_staked: u64,
+    pub last_update: i64,
+    pub bump: u8,
+}
+
+impl Farm {
+    pub const LEN: usize = DISCRIMINATOR_SIZE
+        + (PUBKEY_SIZE * 2)
+        + (U64_SIZE * 2)
+        + U128_SIZE
+        + U64_SIZE
+        + I64_SIZE
+        + U8_SIZE;
+
+    pub fn init(&mut self, config: Pubkey, reward_mint: Pubkey, now: i64, bump: u8) {
+        self.config = config;
+        self.reward_mint = reward_mint;
+        self.reward_rate = 0;
+        self.reward_remaining = 0;
+        self.reward_per_share = 0;
+        self.total_staked = 0;
+        self.last_update = now;
+        self.bump = bump;
+    }
+
+    // Emit rewards for the time since the last update, never more than has been funded
+    pub fn update(&mut self, now: i64) -> Result<()> {
+        if now <= self.last_update {
+            return Ok(());
+        }
+        let elapsed = (now - self.last_update) as u128;
+        self.last_update = now;
+
+        // Nothing accrues while nobody is staked, the rewards stay in the farm
+        if self.total_staked == 0 {
+            return Ok(());
+        }
+
+        let emitted = (self.reward_rate as u128 * elapsed).min(self.reward_remaining as u128);
+        self.reward_remaining -= emitted as u64;
+        self.reward_per_share = self
+            .reward_per_share
+            .checked_add((emitted << 64) / self.total_staked as u128)
+            .ok_or(AmmError::Overflow)?;
+        Ok(())
+    }
+}
+
+#[account]
+pub struct StakePosition {
+    pub farm: Pubkey,
+    pub owner: Pubkey,
+    pub amount: u64,
+    pub reward_per_share_paid: u128, // Farm's `reward_per_share` when rewards were last settled
+    pub pending: u64,                // Settled rewards that have not been claimed yet
+    pub bump: u8,
+}
+
+impl StakePosition {
+    pub const LEN: usize =
+        DISCRIMINATOR_SIZE + (PUBKEY_SIZE * 2) + U64_SIZE + U128_SIZE + U64_SIZE + U8_SIZE;
+
+    // Must be called after `Farm::update` and before the staked amount changes
+    pub fn settle(&mut self, reward_per_share: u128) -> Result<()> {
+        let earned = mul_div(
+            self.amount as u128,
+            reward_per_share - self.reward_per_share_paid,
+            1 << 64,
+        )
+        .ok_or(AmmError::Overflow)?;
+        self.pending =
+            u64::try_from(self.pending as u128 + earned).map_err(|_| AmmError::Overflow)?;
+        self.reward_per_share_paid = reward_per_share;
+        Ok(())
+    }
+}
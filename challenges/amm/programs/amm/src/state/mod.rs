@@ -1,2 +1,17 @@
 pub mod config;
 pub use config::*;
+
+pub mod oracle;
+pub use oracle::*;
+
+pub mod stats;
+pub use stats::*;
+
+pub mod farm;
+pub use farm::*;
+
+pub mod pair;
+pub use pair::*;
+
+pub mod allowlist;
+pub use allowlist::*;
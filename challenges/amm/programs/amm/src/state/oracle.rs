@@ -0,0 +1,115 @@
+use crate::{constants::*, error::AmmError};
+use anchor_lang::prelude::*;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default)]
+pub struct Observation {
+    pub timestamp: i64,
+    pub price_x_cumulative: u128,
+    pub price_y_cumulative: u128,
+}
+
+impl Observation {
+    pub const LEN: usize = I64_SIZE + (U128_SIZE * 2);
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct Twap {
+    pub price_x: u128, // Price of X in Y as Q64.64
+    pub price_y: u128, // Price of Y in X as Q64.64
+    pub window: i64,   // Seconds actually covered by the average
+}
+
+#[account]
+pub struct Oracle {
+    pub config: Pubkey,
+    pub price_x_cumulative: u128, // Q64.64 price of X in Y summed per second
+    pub price_y_cumulative: u128, // Q64.64 price of Y in X summed per second
+    pub last_update: i64,
+    pub index: u8, // Most recent observation
+    pub observations: [Observation; OBSERVATIONS],
+    pub bump: u8,
+}
+
+impl Oracle {
+    pub const LEN: usize = DISCRIMINATOR_SIZE
+        + PUBKEY_SIZE
+        + (U128_SIZE * 2)
+        + I64_SIZE
+        + U8_SIZE
+        + (Observation::LEN * OBSERVATIONS)
+        + U8_SIZE;
+
+    pub fn init(&mut self, config: Pubkey, now: i64, bump: u8) {
+        self.config = config;
+        self.price_x_cumulative = 0;
+        self.price_y_cumulative = 0;
+        self.last_update = now;
+        self.index = 0;
+        self.observations = [Observation::default(); OBSERVATIONS];
+        self.observations[0].timestamp = now;
+        self.bump = bump;
+    }
+
+    // Accumulators extrapolated to `now` using the spot prices held since the last update,
+    // as given by `Pool::oracle_prices` so they follow the pool's curve
+    pub fn cumulative(&self, prices: Option<(u128, u128)>, now: i64) -> (u128, u128) {
+        let elapsed = now.saturating_sub(self.last_update);
+        let (price_x, price_y) = match prices {
+            Some(prices) if elapsed > 0 => prices,
+            _ => return (self.price_x_cumulative, self.price_y_cumulative),
+        };
+
+        // Accumulators are allowed to wrap, only differences between them are meaningful
+        (
+            self.price_x_cumulative
+                .wrapping_add(price_x.wrapping_mul(elapsed as u128)),
+            self.price_y_cumulative
+                .wrapping_add(price_y.wrapping_mul(elapsed as u128)),
+        )
+    }
+
+    // Must be called with the prices from before the reserves change. Without a price
+    // nothing is accumulated, so the time stays with the next price instead of counting as 0
+    pub fn update(&mut self, prices: Option<(u128, u128)>, now: i64) {
+        if now <= self.last_update || prices.is_none() {
+            return;
+        }
+
+        let (price_x_cumulative, price_y_cumulative) = self.cumulative(prices, now);
+        self.price_x_cumulative = price_x_cumulative;
+        self.price_y_cumulative = price_y_cumulative;
+        self.last_update = now;
+
+        let latest = self.observations[self.index as usize];
+        if now - latest.timestamp >= OBSERVATION_INTERVAL {
+            self.index = ((self.index as usize + 1) % OBSERVATIONS) as u8;
+            self.observations[self.index as usize] = Observation {
+                timestamp: now,
+                price_x_cumulative,
+                price_y_cumulative,
+            };
+        }
+    }
+
+    pub fn twap(&self, prices: Option<(u128, u128)>, now: i64, window: u32) -> Result<Twap> {
+        require!(window > 0, AmmError::InvalidAmount);
+        let target = now - window as i64;
+
+        // Walk back from the newest observation to the first one at or before the target
+        let observation = (0..OBSERVATIONS)
+            .map(|i| self.observations[(self.index as usize + OBSERVATIONS - i) % OBSERVATIONS])
+            .find(|o| o.timestamp != 0 && o.timestamp <= target)
+            .ok_or(AmmError::OracleWindowTooLong)?;
+
+        let (price_x_cumulative, price_y_cumulative) = self.cumulative(prices, now);
+        let elapsed = now - observation.timestamp;
+
+        Ok(Twap {
+            price_x: price_x_cumulative.wrapping_sub(observation.price_x_cumulative)
+                / elapsed as u128,
+            price_y: price_y_cumulative.wrapping_sub(observation.price_y_cumulative)
+                / elapsed as u128,
+            window: elapsed,
+        })
+    }
+}
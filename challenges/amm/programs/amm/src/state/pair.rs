@@ -0,0 +1,22 @@
+use crate::constants::*;
+use anchor_lang::prelude::*;
+
+// Canonical pool for a mint pair, at [PAIR_SEED, mint_x, mint_y] with mint_x < mint_y
+#[account]
+pub struct Pair {
+    pub mint_x: Pubkey,
+    pub mint_y: Pubkey,
+    pub config: Pubkey,
+    pub bump: u8,
+}
+
+impl Pair {
+    pub const LEN: usize = DISCRIMINATOR_SIZE + (PUBKEY_SIZE * 3) + U8_SIZE;
+
+    pub fn init(&mut self, mint_x: Pubkey, mint_y: Pubkey, config: Pubkey, bump: u8) {
+        self.mint_x = mint_x;
+        self.mint_y = mint_y;
+        self.config = config;
+        self.bump = bump;
+    }
+}
@@ -0,0 +1,57 @@
+use crate::constants::*;
+use anchor_lang::prelude::*;
+
+// Running totals of a pool's activity, so clients can read them without an indexer.
+// Counters saturate instead of failing, stats must never block trading.
+#[account]
+pub struct Stats {
+    pub config: Pubkey,
+    pub volume_x: u128,   // X swapped into and out of the pool
+    pub volume_y: u128,   // Y swapped into and out of the pool
+    pub fees_x: u128,     // Swap fees paid in X
+    pub fees_y: u128,     // Swap fees paid in Y
+    pub deposits_x: u128, // X added as liquidity
+    pub deposits_y: u128, // Y added as liquidity
+    pub swap_count: u64,
+    pub deposit_count: u64,
+    pub last_swap: i64,
+    pub bump: u8,
+}
+
+impl Stats {
+    pub const LEN: usize =
+        DISCRIMINATOR_SIZE + PUBKEY_SIZE + (U128_SIZE * 6) + (U64_SIZE * 2) + I64_SIZE + U8_SIZE;
+
+    pub fn init(&mut self, config: Pubkey, bump: u8) {
+        self.config = config;
+        self.volume_x = 0;
+        self.volume_y = 0;
+        self.fees_x = 0;
+        self.fees_y = 0;
+        self.deposits_x = 0;
+        self.deposits_y = 0;
+        self.swap_count = 0;
+        self.deposit_count = 0;
+        self.last_swap = 0;
+        self.bump = bump;
+    }
+
+    // `fee` is charged on the input side
+    pub fn record_swap(&mut self, is_x: bool, amount_in: u64, amount_out: u64, fee: u64, now: i64) {
+        let (volume_in, volume_out, fees) = match is_x {
+            true => (&mut self.volume_x, &mut self.volume_y, &mut self.fees_x),
+            false => (&mut self.volume_y, &mut self.volume_x, &mut self.fees_y),
+        };
+        *volume_in = volume_in.saturating_add(amount_in as u128);
+        *volume_out = volume_out.saturating_add(amount_out as u128);
+        *fees = fees.saturating_add(fee as u128);
+        self.swap_count = self.swap_count.saturating_add(1);
+        self.last_swap = now;
+    }
+
+    pub fn record_deposit(&mut self, amount_x: u64, amount_y: u64) {
+        self.deposits_x = self.deposits_x.saturating_add(amount_x as u128);
+        self.deposits_y = self.deposits_y.saturating_add(amount_y as u128);
+        self.deposit_count = self.deposit_count.saturating_add(1);
+    }
+}
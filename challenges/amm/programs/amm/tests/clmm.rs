@@ -0,0 +1,204 @@
+mod common;
+
+use amm::{
+    clmm::math::{
+        amount_x_delta, amount_y_delta, sqrt_price_at_tick, swap_step, tick_at_sqrt_price, ONE,
+    },
+    ClPool, Position, Tick, MAX_TICK, MIN_TICK, TICK_BITMAP_WORDS,
+};
+use anchor_lang::prelude::Pubkey;
+use common::Rng;
+
+const ROUNDS: usize = 256;
+const STEPS: usize = 64;
+
+fn pool(tick_spacing: u16, tick_current: i32) -> ClPool {
+    ClPool {
+        mint_x: Pubkey::new_unique(),
+        mint_y: Pubkey::new_unique(),
+        tick_spacing,
+        fee: 30,
+        sqrt_price: sqrt_price_at_tick(tick_current).unwrap(),
+        tick_current,
+        liquidity: 0,
+        fee_growth_global_x: 0,
+        fee_growth_global_y: 0,
+        bitmap: [0; TICK_BITMAP_WORDS],
+        bump: 0,
+    }
+}
+
+fn tick(index: i32) -> Tick {
+    Tick {
+        pool: Pubkey::default(),
+        index,
+        liquidity_gross: 0,
+        liquidity_net: 0,
+        fee_growth_outside_x: 0,
+        fee_growth_outside_y: 0,
+        bump: 0,
+    }
+}
+
+fn position(tick_lower: i32, tick_upper: i32) -> Position {
+    Position {
+        pool: Pubkey::default(),
+        owner: Pubkey::default(),
+        tick_lower,
+        tick_upper,
+        liquidity: 0,
+        fee_growth_inside_x_last: 0,
+        fee_growth_inside_y_last: 0,
+        tokens_owed_x: 0,
+        tokens_owed_y: 0,
+        bump: 0,
+    }
+}
+
+#[test]
+fn sqrt_price_follows_the_tick() {
+    assert_eq!(sqrt_price_at_tick(0).unwrap(), ONE);
+    assert!(sqrt_price_at_tick(MIN_TICK - 1).is_err());
+    assert!(sqrt_price_at_tick(MAX_TICK + 1).is_err());
+
+    for tick in [-200_000, -10_000, -1, 1, 10_000, 200_000] {
+        let expected = (tick as f64 / 2.0 * 0.0001f64.ln_1p()).exp();
+        let actual = sqrt_price_at_tick(tick).unwrap() as f64 / ONE as f64;
+        assert!(
+            ((actual - expected) / expected).abs() < 1e-12,
+            "tick {tick}: {actual} != {expected}"
+        );
+    }
+}
+
+#[test]
+fn tick_at_sqrt_price_inverts_sqrt_price_at_tick() {
+    let mut rng = Rng(0x5eed_c1a1);
+    for _ in 0..ROUNDS {
+        let tick = rng.tick(MIN_TICK, MAX_TICK - 1);
+        let price = sqrt_price_at_tick(tick).unwrap();
+        let next = sqrt_price_at_tick(tick + 1).unwrap();
+        assert!(price < next);
+        assert_eq!(tick_at_sqrt_price(price).unwrap(), tick);
+        assert_eq!(tick_at_sqrt_price(next - 1).unwrap(), tick);
+    }
+}
+
+#[test]
+fn swap_steps_never_pay_out_more_than_they_take() {
+    let mut rng = Rng(0xc1a1_57e9);
+    for _ in 0..ROUNDS {
+        let liquidity = rng.range(1_000, 1_000_000_000_000) as u128;
+        let lower = rng.tick(-50_000, 0);
+        let upper = rng.tick(1, 50_000);
+        let (a, b) = (
+            sqrt_price_at_tick(lower).unwrap(),
+            sqrt_price_at_tick(upper).unwrap(),
+        );
+        let mut price = sqrt_price_at_tick(rng.tick(lower, upper)).unwrap();
+
+        for _ in 0..STEPS {
+            let is_x = rng.next() % 2 == 0;
+            let remaining = rng.range(1, 1_000_000_000);
+            let target = match is_x {
+                true => a,
+                false => b,
+            };
+            let step = swap_step(price, target, liquidity, remaining, 30).unwrap();
+            assert!(step.amount_in + step.fee <= remaining);
+
+            // The input covers the move between the two prices, the output never exceeds it
+            let (held_in, held_out) = match is_x {
+                true => (
+                    amount_x_delta(step.sqrt_price, price, liquidity, false).unwrap(),
+                    amount_y_delta(step.sqrt_price, price, liquidity, false).unwrap(),
+                ),
+                false => (
+                    amount_y_delta(price, step.sqrt_price, liquidity, false).unwrap(),
+                    amount_x_delta(price, step.sqrt_price, liquidity, false).unwrap(),
+                ),
+            };
+            assert!(step.amount_in as u128 >= held_in);
+            assert!(step.amount_out as u128 <= held_out);
+            price = step.sqrt_price;
+        }
+    }
+}
+
+#[test]
+fn round_trip_swap_never_returns_more() {
+    let mut rng = Rng(0x7e57_c1a1);
+    for _ in 0..ROUNDS {
+        let liquidity = rng.range(1_000_000, 1_000_000_000_000) as u128;
+        let price = sqrt_price_at_tick(rng.tick(-1_000, 1_000)).unwrap();
+        let (min, max) = (
+            sqrt_price_at_tick(-50_000).unwrap(),
+            sqrt_price_at_tick(50_000).unwrap(),
+        );
+
+        let amount = rng.range(1, 1_000_000);
+        let there = swap_step(price, min, liquidity, amount, 0).unwrap();
+        if there.amount_out == 0 {
+            continue;
+        }
+        let back = swap_step(there.sqrt_price, max, liquidity, there.amount_out, 0).unwrap();
+        assert!(back.amount_out <= there.amount_in);
+    }
+}
+
+#[test]
+fn next_initialized_tick_matches_a_scan() {
+    let mut rng = Rng(0xb17_c1a1);
+    for spacing in [1u16, 10, 60] {
+        let mut pool = pool(spacing, 0);
+        let (min, max) = pool.tick_bounds();
+        let mut ticks = vec![];
+        for _ in 0..STEPS {
+            let tick = rng.tick(min / spacing as i32, max / spacing as i32) * spacing as i32;
+            if !ticks.contains(&tick) {
+                pool.flip_tick(tick);
+                ticks.push(tick);
+            }
+        }
+
+        for _ in 0..ROUNDS {
+            let from = rng.tick(min - 100, max + 100);
+            let below = ticks.iter().copied().filter(|t| *t <= from).max();
+            let above = ticks.iter().copied().filter(|t| *t > from).min();
+            assert_eq!(pool.next_initialized_tick(from, true), below);
+            assert_eq!(pool.next_initialized_tick(from, false), above);
+        }
+    }
+}
+
+#[test]
+fn fees_go_only_to_liquidity_in_range() {
+    let mut pool = pool(1, 0);
+    let (mut lower_in, mut upper_in) = (tick(-10), tick(10));
+    let (mut lower_out, mut upper_out) = (tick(20), tick(30));
+    let (mut inside, mut outside) = (position(-10, 10), position(20, 30));
+
+    for (lower, upper, position) in [
+        (&mut lower_in, &mut upper_in, &mut inside),
+        (&mut lower_out, &mut upper_out, &mut outside),
+    ] {
+        lower.update(&pool, 1 << 20, false).unwrap();
+        upper.update(&pool, 1 << 20, true).unwrap();
+        position
+            .update(1 << 20, pool.fee_growth_inside(lower, upper))
+            .unwrap();
+    }
+    pool.liquidity = 1 << 20;
+
+    pool.accrue_fee(true, 5_000).unwrap();
+    pool.accrue_fee(false, 7_000).unwrap();
+
+    inside
+        .update(0, pool.fee_growth_inside(&lower_in, &upper_in))
+        .unwrap();
+    outside
+        .update(0, pool.fee_growth_inside(&lower_out, &upper_out))
+        .unwrap();
+    assert_eq!((inside.tokens_owed_x, inside.tokens_owed_y), (5_000, 7_000));
+    assert_eq!((outside.tokens_owed_x, outside.tokens_owed_y), (0, 0));
+}
@@ -0,0 +1,57 @@
+// Shared by the test crates, each of which only uses part of it
+#![allow(dead_code)]
+
+use amm::{Config, CurveType};
+use anchor_lang::prelude::Pubkey;
+
+// Small xorshift generator so the suites stay deterministic and dependency free
+pub struct Rng(pub u64);
+
+impl Rng {
+    pub fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    pub fn range(&mut self, min: u64, max: u64) -> u64 {
+        min + self.next() % (max - min + 1)
+    }
+
+    pub fn tick(&mut self, min: i32, max: i32) -> i32 {
+        min + (self.next() % (max - min + 1) as u64) as i32
+    }
+}
+
+// Unlocked pool with no authority and no protocol fee
+pub fn config(curve: CurveType, amp: u64, fee: u16) -> Config {
+    Config {
+        seed: 0,
+        authority: None,
+        permissioned: false,
+        mint_x: Pubkey::new_unique(),
+        mint_y: Pubkey::new_unique(),
+        fee,
+        protocol_fee: 0,
+        protocol_fees_x: 0,
+        protocol_fees_y: 0,
+        lp_decimals: 6,
+        curve,
+        initial_amp: amp,
+        target_amp: amp,
+        ramp_start: 0,
+        ramp_end: 0,
+        dynamic_fee: false,
+        min_fee: 0,
+        max_fee: 0,
+        volatility: 0,
+        volatility_updated: 0,
+        flash_loan: 0,
+        flash_loan_is_x: false,
+        locked: false,
+        auth_bump: 0,
+        config_bump: 0,
+        lp_bump: 0,
+    }
+}
@@ -0,0 +1,244 @@
+mod common;
+
+use amm::{
+    helpers::{
+        amount_in_for_exact_out, mul_div, single_sided_swap_amount, sqrt_u128,
+        stable_amount_in_for_exact_out, stable_invariant, stable_swap_out,
+    },
+    quote::Pool,
+    CurveType,
+};
+use common::{config, Rng};
+use constant_product_curve::{ConstantProduct, LiquidityPair};
+
+const ROUNDS: usize = 256;
+const STEPS: usize = 64;
+
+#[test]
+fn k_never_decreases_across_random_swaps_and_deposits() {
+    let mut rng = Rng(0x2545_f491_4f6c_dd1d);
+
+    for _ in 0..ROUNDS {
+        let config = config(CurveType::ConstantProduct, 0, rng.range(0, 1_000) as u16);
+        let mut x = rng.range(1_000_000, 1_000_000_000_000);
+        let mut y = rng.range(1_000_000, 1_000_000_000_000);
+        let mut l = rng.range(1_000, 1_000_000_000_000);
+
+        for _ in 0..STEPS {
+            let k = x as u128 * y as u128;
+            // Priced the way `Swap::swap` and `Deposit::deposit` price against the vaults
+            let pool = Pool {
+                config: &config,
+                vault_x: x,
+                vault_y: y,
+                lp_supply: l,
+                now: 0,
+            };
+            match rng.next() % 3 {
+                0 => {
+                    if let Ok(quote) = pool.swap(true, rng.range(1, x / 2)) {
+                        x += quote.amount_in;
+                        y -= quote.amount_out;
+                    }
+                }
+                1 => {
+                    if let Ok(quote) = pool.swap(false, rng.range(1, y / 2)) {
+                        y += quote.amount_in;
+                        x -= quote.amount_out;
+                    }
+                }
+                _ => {
+                    if let Ok(quote) = pool.deposit(rng.range(1, l), 0, 0) {
+                        x += quote.amount_x;
+                        y += quote.amount_y;
+                        l += quote.lp_amount;
+                    }
+                }
+            }
+            assert!(
+                x as u128 * y as u128 >= k,
+                "k decreased from {} to {} (x: {}, y: {}, l: {})",
+                k,
+                x as u128 * y as u128,
+                x,
+                y,
+                l
+            );
+        }
+    }
+}
+
+#[test]
+fn exact_out_input_always_covers_requested_output() {
+    let mut rng = Rng(0x9e37_79b9_7f4a_7c15);
+
+    for _ in 0..ROUNDS * STEPS {
+        let x = rng.range(1_000_000, 1_000_000_000_000);
+        let y = rng.range(1_000_000, 1_000_000_000_000);
+        let fee = rng.range(0, 1_000) as u16;
+        let amount_out = rng.range(1, y / 2);
+
+        let amount_in = amount_in_for_exact_out(x, y, amount_out, fee).unwrap();
+        let mut curve = ConstantProduct::init(x, y, x, fee, None).unwrap();
+        let res = curve.swap(LiquidityPair::X, amount_in, amount_out).unwrap();
+        assert_eq!(res.deposit, amount_in);
+        assert!(res.withdraw >= amount_out);
+    }
+}
+
+#[test]
+fn single_sided_swap_leaves_a_balanced_deposit() {
+    let mut rng = Rng(0xd1b5_4a32_d192_ed03);
+
+    for _ in 0..ROUNDS * STEPS {
+        let x = rng.range(1_000_000, 1_000_000_000_000);
+        let y = rng.range(1_000_000, 1_000_000_000_000);
+        let fee = rng.range(0, 1_000) as u16;
+        let amount_in = rng.range(1_000, x);
+
+        let swap_amount = single_sided_swap_amount(x, y, amount_in, fee).unwrap();
+        assert!(swap_amount < amount_in);
+
+        let mut curve = ConstantProduct::init(x, y, x, fee, None).unwrap();
+        let res = match curve.swap(LiquidityPair::X, swap_amount, 1) {
+            Ok(res) => res,
+            Err(_) => continue,
+        };
+
+        // Leftover X and swapped Y should sit in the post-swap pool ratio, within rounding
+        let leftover = (amount_in - res.deposit) as u128;
+        let (x2, y2) = (
+            x as u128 + res.deposit as u128,
+            y as u128 - res.withdraw as u128,
+        );
+        let lhs = leftover * y2;
+        let rhs = res.withdraw as u128 * x2;
+        let tolerance = lhs.max(rhs) / 1_000_000 + x2 + y2;
+        assert!(
+            lhs.abs_diff(rhs) <= tolerance,
+            "unbalanced single sided deposit: {} vs {}",
+            lhs,
+            rhs
+        );
+    }
+}
+
+#[test]
+fn sqrt_rounds_down() {
+    let mut rng = Rng(0x94d0_49bb_1331_11eb);
+
+    for _ in 0..ROUNDS * STEPS {
+        let n = rng.next() as u128 * rng.next() as u128;
+        let root = sqrt_u128(n);
+        assert!(root * root <= n);
+        assert!((root + 1).checked_mul(root + 1).map_or(true, |sq| sq > n));
+    }
+    assert_eq!(
+        sqrt_u128(u64::MAX as u128 * u64::MAX as u128),
+        u64::MAX as u128
+    );
+}
+
+#[test]
+fn stable_invariant_never_decreases_across_random_swaps() {
+    let mut rng = Rng(0xbf58_476d_1ce4_e5b9);
+
+    for _ in 0..ROUNDS {
+        let mut x = rng.range(1_000_000, 1_000_000_000_000);
+        let mut y = rng.range(1_000_000, 1_000_000_000_000);
+        let amp = rng.range(1, 10_000);
+        let fee = rng.range(0, 1_000) as u16;
+
+        for _ in 0..STEPS {
+            let d = stable_invariant(x, y, amp).unwrap();
+            let is_x = rng.next() % 2 == 0;
+            let (reserve_in, reserve_out) = match is_x {
+                true => (x, y),
+                false => (y, x),
+            };
+            let amount = rng.range(1, reserve_in / 2);
+            let (out, _) = stable_swap_out(reserve_in, reserve_out, amount, amp, fee).unwrap();
+            if out == 0 {
+                continue;
+            }
+            match is_x {
+                true => {
+                    x += amount;
+                    y -= out;
+                }
+                false => {
+                    y += amount;
+                    x -= out;
+                }
+            }
+            let d2 = stable_invariant(x, y, amp).unwrap();
+            assert!(
+                d2 >= d,
+                "D decreased from {} to {} (x: {}, y: {}, amp: {})",
+                d,
+                d2,
+                x,
+                y,
+                amp
+            );
+        }
+    }
+}
+
+#[test]
+fn stable_exact_out_input_always_covers_requested_output() {
+    let mut rng = Rng(0xc2b2_ae3d_27d4_eb4f);
+
+    for _ in 0..ROUNDS * STEPS {
+        let x = rng.range(1_000_000, 1_000_000_000_000);
+        let y = rng.range(1_000_000, 1_000_000_000_000);
+        let amp = rng.range(1, 10_000);
+        let fee = rng.range(0, 1_000) as u16;
+        let amount_out = rng.range(1, y / 2);
+
+        let amount_in = stable_amount_in_for_exact_out(x, y, amount_out, amp, fee).unwrap();
+        let (out, _) = stable_swap_out(x, y, amount_in, amp, fee).unwrap();
+        assert!(
+            out >= amount_out,
+            "paid {} for {} but wanted {}",
+            amount_in,
+            out,
+            amount_out
+        );
+    }
+}
+
+#[test]
+fn stable_swap_beats_constant_product_at_the_peg() {
+    let mut rng = Rng(0x6a09_e667_f3bc_c908);
+
+    for _ in 0..ROUNDS * STEPS {
+        let x = rng.range(1_000_000_000, 1_000_000_000_000);
+        let y = x;
+        let fee = rng.range(0, 1_000) as u16;
+        let amount = rng.range(x / 1_000, x / 10);
+
+        let (stable, _) = stable_swap_out(x, y, amount, 100, fee).unwrap();
+        let mut curve = ConstantProduct::init(x, y, x, fee, None).unwrap();
+        let res = curve.swap(LiquidityPair::X, amount, 0).unwrap();
+        assert!(stable >= res.withdraw);
+    }
+}
+
+#[test]
+fn mul_div_handles_products_wider_than_u128() {
+    let mut rng = Rng(0xa076_1d64_78bd_642f);
+
+    for _ in 0..ROUNDS * STEPS {
+        let a = (rng.next() as u128) << 64 | rng.next() as u128;
+        let b = (rng.next() as u128) << 64 | rng.next() as u128;
+        assert_eq!(mul_div(a, b, b), Some(a));
+        assert_eq!(mul_div(a, b, a), Some(b));
+        if a > 1 && b > 1 {
+            assert_eq!(mul_div(a, b, 1), a.checked_mul(b));
+        }
+    }
+    assert_eq!(mul_div(u128::MAX, u128::MAX, u128::MAX), Some(u128::MAX));
+    assert_eq!(mul_div(u128::MAX, 2, 1), None);
+    assert_eq!(mul_div(1, 1, 0), None);
+}
@@ -0,0 +1,115 @@
+mod common;
+
+use amm::{Farm, StakePosition};
+use anchor_lang::prelude::Pubkey;
+use common::Rng;
+
+const ROUNDS: usize = 256;
+const STEPS: usize = 64;
+
+fn farm(reward_rate: u64, reward_remaining: u64) -> Farm {
+    Farm {
+        config: Pubkey::new_unique(),
+        reward_mint: Pubkey::new_unique(),
+        reward_rate,
+        reward_remaining,
+        reward_per_share: 0,
+        total_staked: 0,
+        last_update: 0,
+        bump: 0,
+    }
+}
+
+fn position() -> StakePosition {
+    StakePosition {
+        farm: Pubkey::default(),
+        owner: Pubkey::default(),
+        amount: 0,
+        reward_per_share_paid: 0,
+        pending: 0,
+        bump: 0,
+    }
+}
+
+// Mirrors `Stake::stake_lp` and `Stake::unstake_lp`
+fn restake(farm: &mut Farm, position: &mut StakePosition, amount: u64, now: i64) {
+    farm.update(now).unwrap();
+    position.settle(farm.reward_per_share).unwrap();
+    farm.total_staked = farm.total_staked - position.amount + amount;
+    position.amount = amount;
+}
+
+#[test]
+fn stakers_never_earn_more_than_was_emitted() {
+    let mut rng = Rng(0x2545_f491_4f6c_dd1d);
+
+    for _ in 0..ROUNDS {
+        let funded = rng.range(1, 1_000_000_000_000);
+        let mut farm = farm(rng.range(1, 1_000_000), funded);
+        let mut positions: Vec<StakePosition> = (0..4).map(|_| position()).collect();
+        let mut now = 0;
+
+        for _ in 0..STEPS {
+            now += rng.range(0, 10_000) as i64;
+            let i = (rng.next() % 4) as usize;
+            let amount = rng.range(0, 1_000_000_000_000);
+            restake(&mut farm, &mut positions[i], amount, now);
+
+            let earned: u128 = positions.iter().map(|p| p.pending as u128).sum();
+            let emitted = (funded - farm.reward_remaining) as u128;
+            assert!(earned <= emitted, "earned {} of {}", earned, emitted);
+        }
+    }
+}
+
+#[test]
+fn rewards_split_pro_rata_by_stake() {
+    let mut rng = Rng(0x9e37_79b9_7f4a_7c15);
+
+    for _ in 0..ROUNDS * STEPS {
+        let rate = rng.range(1, 1_000_000);
+        let mut farm = farm(rate, u64::MAX);
+        let (mut a, mut b) = (position(), position());
+        let (stake_a, stake_b) = (rng.range(1, 1_000_000_000), rng.range(1, 1_000_000_000));
+        restake(&mut farm, &mut a, stake_a, 0);
+        restake(&mut farm, &mut b, stake_b, 0);
+
+        let elapsed = rng.range(1, 1_000_000) as i64;
+        restake(&mut farm, &mut a, 0, elapsed);
+        restake(&mut farm, &mut b, 0, elapsed);
+
+        let emitted = rate as u128 * elapsed as u128;
+        let total = stake_a as u128 + stake_b as u128;
+        for (position, stake) in [(a, stake_a), (b, stake_b)] {
+            let share = emitted * stake as u128 / total;
+            assert!(position.pending as u128 <= share);
+            assert!(position.pending as u128 + 1 >= share);
+        }
+    }
+}
+
+#[test]
+fn nothing_accrues_while_nobody_is_staked() {
+    let mut farm = farm(1_000, 1_000_000);
+    farm.update(500).unwrap();
+    assert_eq!(farm.reward_remaining, 1_000_000);
+    assert_eq!(farm.reward_per_share, 0);
+
+    // Time spent empty isn't paid out to the first staker either
+    let mut position = position();
+    restake(&mut farm, &mut position, 100, 500);
+    restake(&mut farm, &mut position, 100, 600);
+    assert_eq!(position.pending, 100_000);
+}
+
+#[test]
+fn emission_stops_once_the_funding_runs_out() {
+    let mut farm = farm(1_000, 250_000);
+    let mut position = position();
+    restake(&mut farm, &mut position, 7, 0);
+    restake(&mut farm, &mut position, 7, 1_000);
+
+    assert_eq!(farm.reward_remaining, 0);
+    assert!(position.pending <= 250_000);
+    assert!(position.pending >= 250_000 - 1);
+}
@@ -0,0 +1,938 @@
+use amm::{
+    accounts, error::AmmError, instruction, quote::Pool, Config, CurveType, Pair, Stats,
+    ALLOWLIST_SEED, AUTH_SEED, CONFIG_SEED, LP_SEED, MINIMUM_LIQUIDITY, ORACLE_SEED, PAIR_SEED,
+    STATS_SEED,
+};
+use anchor_lang::{
+    solana_program::{
+        account_info::AccountInfo,
+        entrypoint::ProgramResult,
+        instruction::{AccountMeta, Instruction},
+        program_pack::Pack,
+        pubkey::Pubkey,
+        system_instruction, system_program,
+    },
+    AccountDeserialize, InstructionData, ToAccountMetas,
+};
+use anchor_spl::{
+    associated_token::{
+        get_associated_token_address, get_associated_token_address_with_program_id,
+    },
+    token::spl_token,
+    token_2022::spl_token_2022::{
+        self, extension::StateWithExtensions, state::Account as TokenAccount,
+    },
+};
+use solana_program_test::{processor, BanksClientError, ProgramTest, ProgramTestContext};
+use solana_sdk::{
+    instruction::InstructionError,
+    signature::{Keypair, Signer},
+    transaction::{Transaction, TransactionError},
+};
+use spl_associated_token_account::instruction::create_associated_token_account;
+
+const NEVER: i64 = i64::MAX;
+const SUPPLY: u64 = 1_000_000_000_000;
+
+// Anchor's entrypoint ties the accounts slice to the account lifetimes, which `processor!` can't express
+fn process(program_id: &Pubkey, accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    let accounts = Box::leak(Box::new(accounts.to_vec()));
+    amm::entry(program_id, accounts, data)
+}
+
+// A bank with the amm program, two funded mints and the PDAs of one pool over them
+struct Test {
+    ctx: ProgramTestContext,
+    token_program: Pubkey,
+    mint_x: Pubkey,
+    mint_y: Pubkey,
+    seed: u64,
+    permissioned: bool,
+}
+
+impl Test {
+    async fn new() -> Self {
+        Test::with_token_program(spl_token::ID).await
+    }
+
+    // Mints, vaults and LP mint all owned by `token_program`
+    async fn with_token_program(token_program: Pubkey) -> Self {
+        let program = ProgramTest::new("amm", amm::ID, processor!(process));
+        let mut test = Test {
+            ctx: program.start_with_context().await,
+            token_program,
+            mint_x: Pubkey::default(),
+            mint_y: Pubkey::default(),
+            seed: 42,
+            permissioned: false,
+        };
+        test.mint_x = test.create_mint().await;
+        test.mint_y = test.create_mint().await;
+        test
+    }
+
+    fn payer(&self) -> Pubkey {
+        self.ctx.payer.pubkey()
+    }
+
+    fn ata(&self, owner: &Pubkey, mint: &Pubkey) -> Pubkey {
+        get_associated_token_address_with_program_id(owner, mint, &self.token_program)
+    }
+
+    fn pda(&self, seeds: &[&[u8]]) -> Pubkey {
+        Pubkey::find_program_address(seeds, &amm::ID).0
+    }
+
+    fn config(&self) -> Pubkey {
+        self.pda(&[CONFIG_SEED, &self.seed.to_le_bytes()])
+    }
+
+    fn auth(&self) -> Pubkey {
+        self.pda(&[AUTH_SEED, self.config().as_ref()])
+    }
+
+    fn mint_lp(&self) -> Pubkey {
+        self.pda(&[LP_SEED, self.config().as_ref()])
+    }
+
+    fn oracle(&self) -> Pubkey {
+        self.pda(&[ORACLE_SEED, self.config().as_ref()])
+    }
+
+    fn pair(&self) -> Pubkey {
+        self.pda(&[PAIR_SEED, self.mint_x.as_ref(), self.mint_y.as_ref()])
+    }
+
+    fn stats(&self) -> Pubkey {
+        self.pda(&[STATS_SEED, self.config().as_ref()])
+    }
+
+    fn allowlist_entry(&self, wallet: Pubkey) -> Pubkey {
+        self.pda(&[ALLOWLIST_SEED, self.config().as_ref(), wallet.as_ref()])
+    }
+
+    // The wallet's allowlist entry, if the authority has created one
+    async fn find_allowlist_entry(&mut self, wallet: Pubkey) -> Option<Pubkey> {
+        let entry = self.allowlist_entry(wallet);
+        self.ctx
+            .banks_client
+            .get_account(entry)
+            .await
+            .unwrap()
+            .map(|_| entry)
+    }
+
+    async fn send(
+        &mut self,
+        ix: Instruction,
+        signers: &[&Keypair],
+    ) -> Result<(), BanksClientError> {
+        // A fresh blockhash keeps repeated identical transactions from being deduplicated
+        let blockhash = self.ctx.get_new_latest_blockhash().await.unwrap();
+        let mut all = vec![&self.ctx.payer];
+        all.extend_from_slice(signers);
+        let tx = Transaction::new_signed_with_payer(
+            &[ix],
+            Some(&self.payer()),
+            &all,
+            blockhash,
+        );
+        self.ctx.banks_client.process_transaction(tx).await
+    }
+
+    // Creates a mint and hands the whole supply to the payer
+    async fn create_mint(&mut self) -> Pubkey {
+        let mint = Keypair::new();
+        let rent = self.ctx.banks_client.get_rent().await.unwrap();
+        let payer = self.payer();
+
+        self.send(
+            system_instruction::create_account(
+                &payer,
+                &mint.pubkey(),
+                rent.minimum_balance(spl_token::state::Mint::LEN),
+                spl_token::state::Mint::LEN as u64,
+                &self.token_program,
+            ),
+            &[&mint],
+        )
+        .await
+        .unwrap();
+        self.send(
+            spl_token_2022::instruction::initialize_mint2(
+                &self.token_program,
+                &mint.pubkey(),
+                &payer,
+                None,
+                6,
+            )
+            .unwrap(),
+            &[],
+        )
+        .await
+        .unwrap();
+        self.send(
+            create_associated_token_account(&payer, &payer, &mint.pubkey(), &self.token_program),
+            &[],
+        )
+        .await
+        .unwrap();
+        self.send(
+            spl_token_2022::instruction::mint_to(
+                &self.token_program,
+                &mint.pubkey(),
+                &self.ata(&payer, &mint.pubkey()),
+                &payer,
+                &[],
+                SUPPLY,
+            )
+            .unwrap(),
+            &[],
+        )
+        .await
+        .unwrap();
+        mint.pubkey()
+    }
+
+    async fn balance(&mut self, owner: Pubkey, mint: Pubkey) -> u64 {
+        let address = self.ata(&owner, &mint);
+        let account = self
+            .ctx
+            .banks_client
+            .get_account(address)
+            .await
+            .unwrap()
+            .unwrap();
+        // Token-2022 accounts carry extensions after the base layout
+        StateWithExtensions::<TokenAccount>::unpack(&account.data)
+            .unwrap()
+            .base
+            .amount
+    }
+
+    async fn fetch_config(&mut self) -> Config {
+        let account = self
+            .ctx
+            .banks_client
+            .get_account(self.config())
+            .await
+            .unwrap()
+            .unwrap();
+        Config::try_deserialize(&mut account.data.as_slice()).unwrap()
+    }
+
+    async fn fetch_pair(&mut self) -> Pair {
+        let account = self
+            .ctx
+            .banks_client
+            .get_account(self.pair())
+            .await
+            .unwrap()
+            .unwrap();
+        Pair::try_deserialize(&mut account.data.as_slice()).unwrap()
+    }
+
+    async fn fetch_stats(&mut self) -> Stats {
+        let account = self
+            .ctx
+            .banks_client
+            .get_account(self.stats())
+            .await
+            .unwrap()
+            .unwrap();
+        Stats::try_deserialize(&mut account.data.as_slice()).unwrap()
+    }
+
+    async fn initialize(&mut self, mint_y: Pubkey, fee: u16) -> Result<(), BanksClientError> {
+        let authority = Some(self.payer());
+        self.initialize_with(mint_y, fee, authority, CurveType::ConstantProduct, 0, 6)
+            .await
+    }
+
+    async fn initialize_with(
+        &mut self,
+        mint_y: Pubkey,
+        fee: u16,
+        authority: Option<Pubkey>,
+        curve: CurveType,
+        amp: u64,
+        lp_decimals: u8,
+    ) -> Result<(), BanksClientError> {
+        let ix = Instruction {
+            program_id: amm::ID,
+            accounts: accounts::Initialize {
+                initializer: self.payer(),
+                mint_x: self.mint_x,
+                mint_y,
+                mint_lp: self.mint_lp(),
+                vault_x: self.ata(&self.auth(), &self.mint_x),
+                vault_y: self.ata(&self.auth(), &mint_y),
+                vault_lp: self.ata(&self.auth(), &self.mint_lp()),
+                auth: self.auth(),
+                config: self.config(),
+                oracle: self.oracle(),
+                stats: self.stats(),
+                associated_token_program: anchor_spl::associated_token::ID,
+                token_program: self.token_program,
+                system_program: system_program::ID,
+            }
+            .to_account_metas(None),
+            data: instruction::Initialize {
+                seed: self.seed,
+                fee,
+                authority,
+                curve,
+                amp,
+                lp_decimals,
+                permissioned: self.permissioned,
+            }
+            .data(),
+        };
+        self.send(ix, &[]).await
+    }
+
+    async fn deposit(
+        &mut self,
+        amount: u64,
+        max_x: u64,
+        max_y: u64,
+        expiration: i64,
+    ) -> Result<(), BanksClientError> {
+        let user = self.payer();
+        let allowlist_entry = self.find_allowlist_entry(user).await;
+        let ix = Instruction {
+            program_id: amm::ID,
+            accounts: accounts::Deposit {
+                user,
+                mint_x: self.mint_x,
+                mint_y: self.mint_y,
+                mint_lp: self.mint_lp(),
+                vault_x: self.ata(&self.auth(), &self.mint_x),
+                vault_y: self.ata(&self.auth(), &self.mint_y),
+                user_x: self.ata(&user, &self.mint_x),
+                user_y: self.ata(&user, &self.mint_y),
+                user_lp: self.ata(&user, &self.mint_lp()),
+                vault_lp: self.ata(&self.auth(), &self.mint_lp()),
+                auth: self.auth(),
+                config: self.config(),
+                oracle: self.oracle(),
+                stats: self.stats(),
+                allowlist_entry,
+                associated_token_program: anchor_spl::associated_token::ID,
+                token_program: self.token_program,
+                system_program: system_program::ID,
+            }
+            .to_account_metas(None),
+            data: instruction::Deposit {
+                amount,
+                max_x,
+                max_y,
+                expiration,
+            }
+            .data(),
+        };
+        self.send(ix, &[]).await
+    }
+
+    async fn swap(
+        &mut self,
+        is_x: bool,
+        amount: u64,
+        min: u64,
+        expiration: i64,
+    ) -> Result<(), BanksClientError> {
+        let user = self.payer();
+        let allowlist_entry = self.find_allowlist_entry(user).await;
+        let ix = Instruction {
+            program_id: amm::ID,
+            accounts: accounts::Swap {
+                user,
+                mint_x: self.mint_x,
+                mint_y: self.mint_y,
+                mint_lp: self.mint_lp(),
+                vault_x: self.ata(&self.auth(), &self.mint_x),
+                vault_y: self.ata(&self.auth(), &self.mint_y),
+                user_x: self.ata(&user, &self.mint_x),
+                user_y: self.ata(&user, &self.mint_y),
+                user_lp: self.ata(&user, &self.mint_lp()),
+                auth: self.auth(),
+                config: self.config(),
+                oracle: self.oracle(),
+                stats: self.stats(),
+                allowlist_entry,
+                associated_token_program: anchor_spl::associated_token::ID,
+                token_program: self.token_program,
+                system_program: system_program::ID,
+            }
+            .to_account_metas(None),
+            data: instruction::Swap {
+                is_x,
+                amount,
+                min,
+                expiration,
+            }
+            .data(),
+        };
+        self.send(ix, &[]).await
+    }
+
+    // Single hop route through the pool, with `vault_x` standing in for the pool's X vault
+    async fn route_swap(
+        &mut self,
+        vault_x: Pubkey,
+        amount_in: u64,
+    ) -> Result<(), BanksClientError> {
+        let user = self.payer();
+        let mut accounts = accounts::RouteSwap {
+            user,
+            user_in: self.ata(&user, &self.mint_x),
+            user_out: self.ata(&user, &self.mint_y),
+            token_program: self.token_program,
+        }
+        .to_account_metas(None);
+        accounts.extend([
+            AccountMeta::new(self.config(), false),
+            AccountMeta::new_readonly(self.auth(), false),
+            AccountMeta::new(self.oracle(), false),
+            AccountMeta::new(self.stats(), false),
+            AccountMeta::new_readonly(self.mint_lp(), false),
+            AccountMeta::new(vault_x, false),
+            AccountMeta::new(self.ata(&self.auth(), &self.mint_y), false),
+            AccountMeta::new_readonly(self.mint_x, false),
+            AccountMeta::new_readonly(self.mint_y, false),
+        ]);
+        let ix = Instruction {
+            program_id: amm::ID,
+            accounts,
+            data: instruction::RouteSwap {
+                hops: 1,
+                amount_in,
+                min_out: 1,
+                expiration: NEVER,
+            }
+            .data(),
+        };
+        self.send(ix, &[]).await
+    }
+
+    // Token account for `mint` owned by `owner` that is not its ATA, funded with `amount`
+    async fn create_token_account(&mut self, mint: Pubkey, owner: Pubkey, amount: u64) -> Pubkey {
+        let account = Keypair::new();
+        let rent = self.ctx.banks_client.get_rent().await.unwrap();
+        let payer = self.payer();
+
+        self.send(
+            system_instruction::create_account(
+                &payer,
+                &account.pubkey(),
+                rent.minimum_balance(spl_token::state::Account::LEN),
+                spl_token::state::Account::LEN as u64,
+                &self.token_program,
+            ),
+            &[&account],
+        )
+        .await
+        .unwrap();
+        self.send(
+            spl_token_2022::instruction::initialize_account3(
+                &self.token_program,
+                &account.pubkey(),
+                &mint,
+                &owner,
+            )
+            .unwrap(),
+            &[],
+        )
+        .await
+        .unwrap();
+        self.send(
+            spl_token_2022::instruction::transfer_checked(
+                &self.token_program,
+                &self.ata(&payer, &mint),
+                &mint,
+                &account.pubkey(),
+                &payer,
+                &[],
+                amount,
+                6,
+            )
+            .unwrap(),
+            &[],
+        )
+        .await
+        .unwrap();
+        account.pubkey()
+    }
+
+    async fn withdraw(&mut self, amount: u64) -> Result<(), BanksClientError> {
+        let user = self.payer();
+        let ix = Instruction {
+            program_id: amm::ID,
+            accounts: accounts::Withdraw {
+                user,
+                mint_x: self.mint_x,
+                mint_y: self.mint_y,
+                mint_lp: self.mint_lp(),
+                vault_x: self.ata(&self.auth(), &self.mint_x),
+                vault_y: self.ata(&self.auth(), &self.mint_y),
+                user_x: self.ata(&user, &self.mint_x),
+                user_y: self.ata(&user, &self.mint_y),
+                user_lp: self.ata(&user, &self.mint_lp()),
+                auth: self.auth(),
+                config: self.config(),
+                associated_token_program: anchor_spl::associated_token::ID,
+                token_program: self.token_program,
+                system_program: system_program::ID,
+            }
+            .to_account_metas(None),
+            data: instruction::Withdraw {
+                amount,
+                min_x: 1,
+                min_y: 1,
+                expiration: NEVER,
+            }
+            .data(),
+        };
+        self.send(ix, &[]).await
+    }
+
+    // Any of the instructions taking the `Update` accounts
+    async fn update(&mut self, user: &Keypair, data: Vec<u8>) -> Result<(), BanksClientError> {
+        let ix = Instruction {
+            program_id: amm::ID,
+            accounts: accounts::Update {
+                user: user.pubkey(),
+                config: self.config(),
+            }
+            .to_account_metas(None),
+            data,
+        };
+        self.send(ix, &[user]).await
+    }
+
+    async fn lock(&mut self, user: &Keypair) -> Result<(), BanksClientError> {
+        self.update(user, instruction::Lock {}.data()).await
+    }
+
+    async fn register_pair(&mut self) -> Result<(), BanksClientError> {
+        let ix = Instruction {
+            program_id: amm::ID,
+            accounts: accounts::RegisterPair {
+                user: self.payer(),
+                mint_x: self.mint_x,
+                mint_y: self.mint_y,
+                config: self.config(),
+                pair: self.pair(),
+                system_program: system_program::ID,
+            }
+            .to_account_metas(None),
+            data: instruction::RegisterPair {}.data(),
+        };
+        self.send(ix, &[]).await
+    }
+
+    // Contests the pair entry held by `current` with this pool
+    async fn contest_pair(&mut self, current: Pubkey) -> Result<(), BanksClientError> {
+        let ix = Instruction {
+            program_id: amm::ID,
+            accounts: accounts::ContestPair {
+                user: self.payer(),
+                mint_x: self.mint_x,
+                mint_y: self.mint_y,
+                config: self.config(),
+                mint_lp: self.mint_lp(),
+                current,
+                current_mint_lp: self.pda(&[LP_SEED, current.as_ref()]),
+                pair: self.pair(),
+            }
+            .to_account_metas(None),
+            data: instruction::ContestPair {}.data(),
+        };
+        self.send(ix, &[]).await
+    }
+
+    async fn close_pool(&mut self) -> Result<(), BanksClientError> {
+        let user = self.payer();
+        let ix = Instruction {
+            program_id: amm::ID,
+            accounts: accounts::ClosePool {
+                user,
+                recipient: user,
+                mint_x: self.mint_x,
+                mint_y: self.mint_y,
+                mint_lp: self.mint_lp(),
+                vault_x: self.ata(&self.auth(), &self.mint_x),
+                vault_y: self.ata(&self.auth(), &self.mint_y),
+                vault_lp: self.ata(&self.auth(), &self.mint_lp()),
+                treasury_x: self.ata(&user, &self.mint_x),
+                treasury_y: self.ata(&user, &self.mint_y),
+                auth: self.auth(),
+                config: self.config(),
+                oracle: self.oracle(),
+                stats: self.stats(),
+                token_program: self.token_program,
+            }
+            .to_account_metas(None),
+            data: instruction::ClosePool {}.data(),
+        };
+        self.send(ix, &[]).await
+    }
+
+    async fn add_to_allowlist(&mut self, wallet: Pubkey) -> Result<(), BanksClientError> {
+        let ix = Instruction {
+            program_id: amm::ID,
+            accounts: accounts::AddToAllowlist {
+                user: self.payer(),
+                config: self.config(),
+                allowlist_entry: self.allowlist_entry(wallet),
+                system_program: system_program::ID,
+            }
+            .to_account_metas(None),
+            data: instruction::AddToAllowlist { wallet }.data(),
+        };
+        self.send(ix, &[]).await
+    }
+
+    async fn remove_from_allowlist(&mut self, wallet: Pubkey) -> Result<(), BanksClientError> {
+        let ix = Instruction {
+            program_id: amm::ID,
+            accounts: accounts::RemoveFromAllowlist {
+                user: self.payer(),
+                config: self.config(),
+                allowlist_entry: self.allowlist_entry(wallet),
+            }
+            .to_account_metas(None),
+            data: instruction::RemoveFromAllowlist {}.data(),
+        };
+        self.send(ix, &[]).await
+    }
+
+    // Initialized pool holding 1_000_000_000 of each token
+    async fn funded() -> Self {
+        let mut test = Test::new().await;
+        let mint_y = test.mint_y;
+        test.initialize(mint_y, 30).await.unwrap();
+        test.deposit(1, 1_000_000_000, 1_000_000_000, NEVER)
+            .await
+            .unwrap();
+        test
+    }
+}
+
+fn assert_amm_error(result: Result<(), BanksClientError>, error: AmmError) {
+    assert_eq!(
+        result.unwrap_err().unwrap(),
+        TransactionError::InstructionError(0, InstructionError::Custom(error.into()))
+    );
+}
+
+#[tokio::test]
+async fn initialize_and_first_deposit() {
+    let mut test = Test::funded().await;
+    let (payer, auth, mint_lp) = (test.payer(), test.auth(), test.mint_lp());
+
+    assert_eq!(test.balance(auth, test.mint_x).await, 1_000_000_000);
+    assert_eq!(test.balance(auth, test.mint_y).await, 1_000_000_000);
+    assert_eq!(test.balance(auth, mint_lp).await, MINIMUM_LIQUIDITY);
+    assert_eq!(
+        test.balance(payer, mint_lp).await,
+        1_000_000_000 - MINIMUM_LIQUIDITY
+    );
+
+    let config = test.fetch_config().await;
+    assert_eq!(config.mint_x, test.mint_x);
+    assert_eq!(config.mint_y, test.mint_y);
+    assert_eq!(config.fee, 30);
+    assert!(!config.locked);
+}
+
+#[tokio::test]
+async fn swap_pays_out_the_quoted_amount() {
+    let mut test = Test::funded().await;
+    let payer = test.payer();
+    let config = test.fetch_config().await;
+    let quote = Pool {
+        config: &config,
+        vault_x: 1_000_000_000,
+        vault_y: 1_000_000_000,
+        lp_supply: 1_000_000_000,
+        now: 0,
+    }
+    .swap(true, 1_000_000)
+    .unwrap();
+
+    let before = test.balance(payer, test.mint_y).await;
+    test.swap(true, 1_000_000, quote.amount_out, NEVER)
+        .await
+        .unwrap();
+    let after = test.balance(payer, test.mint_y).await;
+    assert_eq!(after - before, quote.amount_out);
+
+    let auth = test.auth();
+    assert_eq!(test.balance(auth, test.mint_x).await, 1_001_000_000);
+}
+
+#[tokio::test]
+async fn stats_count_swaps_and_deposits() {
+    let mut test = Test::funded().await;
+    let stats = test.fetch_stats().await;
+    assert_eq!(stats.config, test.config());
+    assert_eq!(stats.deposit_count, 1);
+    assert_eq!(
+        (stats.deposits_x, stats.deposits_y),
+        (1_000_000_000, 1_000_000_000)
+    );
+    assert_eq!(stats.swap_count, 0);
+
+    test.swap(true, 1_000_000, 1, NEVER).await.unwrap();
+    test.swap(false, 2_000_000, 1, NEVER).await.unwrap();
+    let stats = test.fetch_stats().await;
+    assert_eq!(stats.swap_count, 2);
+    assert!(stats.volume_x > 1_000_000 && stats.volume_y > 2_000_000);
+    assert!(stats.fees_x > 0 && stats.fees_y > stats.fees_x);
+    assert!(stats.last_swap > 0);
+}
+
+#[tokio::test]
+async fn token_2022_pools_accept_deposits_and_swaps() {
+    let mut test = Test::with_token_program(spl_token_2022::ID).await;
+    let (payer, auth, mint_x, mint_y) = (test.payer(), test.auth(), test.mint_x, test.mint_y);
+    test.initialize(mint_y, 30).await.unwrap();
+    test.deposit(1, 1_000_000_000, 1_000_000_000, NEVER)
+        .await
+        .unwrap();
+    assert_eq!(test.balance(auth, mint_x).await, 1_000_000_000);
+
+    let before = test.balance(payer, mint_y).await;
+    test.swap(true, 1_000_000, 1, NEVER).await.unwrap();
+    assert!(test.balance(payer, mint_y).await > before);
+    assert_eq!(test.balance(auth, mint_x).await, 1_001_000_000);
+}
+
+#[tokio::test]
+async fn expired_offers_are_rejected() {
+    let mut test = Test::funded().await;
+    assert_amm_error(
+        test.swap(true, 1_000_000, 1, 0).await,
+        AmmError::OfferExpired,
+    );
+    assert_amm_error(
+        test.deposit(1_000_000, 1_000_000_000, 1_000_000_000, 0)
+            .await,
+        AmmError::OfferExpired,
+    );
+}
+
+#[tokio::test]
+async fn locked_pools_reject_trading() {
+    let mut test = Test::funded().await;
+    let payer = test.ctx.payer.insecure_clone();
+    test.lock(&payer).await.unwrap();
+    assert!(test.fetch_config().await.locked);
+
+    assert_amm_error(
+        test.swap(true, 1_000_000, 1, NEVER).await,
+        AmmError::PoolLocked,
+    );
+    assert_amm_error(
+        test.deposit(1_000_000, 1_000_000_000, 1_000_000_000, NEVER)
+            .await,
+        AmmError::PoolLocked,
+    );
+}
+
+#[tokio::test]
+async fn slippage_limits_are_enforced() {
+    let mut test = Test::funded().await;
+    assert_amm_error(
+        test.swap(true, 1_000_000, 1_000_000, NEVER).await,
+        AmmError::SlippageExceeded,
+    );
+    assert_amm_error(
+        test.deposit(1_000_000, 1, 1, NEVER).await,
+        AmmError::SlippageExceeded,
+    );
+}
+
+#[tokio::test]
+async fn zero_amounts_are_rejected() {
+    let mut test = Test::funded().await;
+    assert_amm_error(test.swap(true, 0, 1, NEVER).await, AmmError::ZeroBalance);
+    assert_amm_error(
+        test.deposit(0, 1_000_000_000, 1_000_000_000, NEVER).await,
+        AmmError::ZeroBalance,
+    );
+}
+
+#[tokio::test]
+async fn initialize_rejects_bad_parameters() {
+    let mut test = Test::new().await;
+    let (mint_x, mint_y) = (test.mint_x, test.mint_y);
+    assert_amm_error(test.initialize(mint_y, 10_001).await, AmmError::InvalidFee);
+    assert_amm_error(test.initialize(mint_x, 30).await, AmmError::IdenticalMints);
+}
+
+#[tokio::test]
+async fn only_the_authority_can_lock() {
+    let mut test = Test::funded().await;
+    let stranger = Keypair::new();
+    assert_amm_error(test.lock(&stranger).await, AmmError::InvalidAuthority);
+}
+
+#[tokio::test]
+async fn route_swap_only_accepts_the_pool_vaults() {
+    let mut test = Test::funded().await;
+    let (auth, mint_x) = (test.auth(), test.mint_x);
+
+    // Owned by auth but not the pool's ATA, so it would price X as nearly empty
+    let fake_vault = test.create_token_account(mint_x, auth, 1).await;
+    assert_amm_error(
+        test.route_swap(fake_vault, 1_000).await,
+        AmmError::InvalidRoute,
+    );
+
+    let vault_x = get_associated_token_address(&auth, &mint_x);
+    test.route_swap(vault_x, 1_000).await.unwrap();
+
+    // Each hop counts towards its pool's stats like a direct swap
+    let stats = test.fetch_stats().await;
+    assert_eq!(stats.swap_count, 1);
+    assert_eq!(stats.volume_x, 1_000);
+}
+
+#[tokio::test]
+async fn first_deposit_must_exceed_the_minimum_liquidity() {
+    let mut test = Test::new().await;
+    let mint_y = test.mint_y;
+    test.initialize(mint_y, 30).await.unwrap();
+    assert_amm_error(
+        test.deposit(1, MINIMUM_LIQUIDITY, MINIMUM_LIQUIDITY, NEVER)
+            .await,
+        AmmError::LiquidityLessThanMinimum,
+    );
+}
+
+#[tokio::test]
+async fn pools_without_an_authority_cannot_be_updated() {
+    let mut test = Test::new().await;
+    let mint_y = test.mint_y;
+    test.initialize_with(mint_y, 30, None, CurveType::ConstantProduct, 0, 6)
+        .await
+        .unwrap();
+    let payer = test.ctx.payer.insecure_clone();
+    assert_amm_error(test.lock(&payer).await, AmmError::NoAuthoritySet);
+}
+
+#[tokio::test]
+async fn withdraw_is_limited_to_the_lp_balance() {
+    let mut test = Test::funded().await;
+    let (payer, mint_lp) = (test.payer(), test.mint_lp());
+    let balance = test.balance(payer, mint_lp).await;
+    assert_amm_error(
+        test.withdraw(balance + 1).await,
+        AmmError::InsufficientBalance,
+    );
+    test.withdraw(balance).await.unwrap();
+}
+
+#[tokio::test]
+async fn initialize_rejects_too_many_lp_decimals() {
+    let mut test = Test::new().await;
+    let mint_y = test.mint_y;
+    assert_amm_error(
+        test.initialize_with(mint_y, 30, None, CurveType::ConstantProduct, 0, 10)
+            .await,
+        AmmError::InvalidPrecision,
+    );
+}
+
+#[tokio::test]
+async fn constant_product_pools_have_no_amplification() {
+    let mut test = Test::funded().await;
+    let payer = test.ctx.payer.insecure_clone();
+    let data = instruction::RampAmp {
+        target_amp: 200,
+        ramp_end: NEVER,
+    }
+    .data();
+    assert_amm_error(test.update(&payer, data).await, AmmError::UnsupportedCurve);
+}
+
+#[tokio::test]
+async fn permissioned_pools_only_trade_with_allowlisted_wallets() {
+    let mut test = Test::new().await;
+    test.permissioned = true;
+    let (payer, mint_y) = (test.payer(), test.mint_y);
+    test.initialize(mint_y, 30).await.unwrap();
+    assert_amm_error(
+        test.deposit(1, 1_000_000_000, 1_000_000_000, NEVER).await,
+        AmmError::NotAllowlisted,
+    );
+
+    test.add_to_allowlist(payer).await.unwrap();
+    test.deposit(1, 1_000_000_000, 1_000_000_000, NEVER)
+        .await
+        .unwrap();
+    test.swap(true, 1_000_000, 1, NEVER).await.unwrap();
+
+    test.remove_from_allowlist(payer).await.unwrap();
+    assert_amm_error(
+        test.swap(true, 1_000_000, 1, NEVER).await,
+        AmmError::NotAllowlisted,
+    );
+}
+
+#[tokio::test]
+async fn permissioned_pools_need_an_authority() {
+    let mut test = Test::new().await;
+    test.permissioned = true;
+    let mint_y = test.mint_y;
+    assert_amm_error(
+        test.initialize_with(mint_y, 30, None, CurveType::ConstantProduct, 0, 6)
+            .await,
+        AmmError::NoAuthoritySet,
+    );
+}
+
+#[tokio::test]
+async fn open_pools_have_no_allowlist() {
+    let mut test = Test::funded().await;
+    let payer = test.payer();
+    assert_amm_error(
+        test.add_to_allowlist(payer).await,
+        AmmError::PoolNotPermissioned,
+    );
+}
+
+#[tokio::test]
+async fn closed_pools_give_up_their_pair() {
+    let mut test = Test::new().await;
+    // Only pools with sorted mints can be registered
+    if test.mint_x > test.mint_y {
+        std::mem::swap(&mut test.mint_x, &mut test.mint_y);
+    }
+    let (payer, mint_y) = (test.payer(), test.mint_y);
+    test.initialize(mint_y, 30).await.unwrap();
+    test.deposit(1, 1_000_000_000, 1_000_000_000, NEVER)
+        .await
+        .unwrap();
+    test.register_pair().await.unwrap();
+    let registered = test.config();
+
+    // A funded pool can't take the pair while the registered one holds liquidity
+    test.seed = 43;
+    test.initialize(mint_y, 30).await.unwrap();
+    test.deposit(1, 1_000_000_000, 1_000_000_000, NEVER)
+        .await
+        .unwrap();
+    assert_amm_error(test.contest_pair(registered).await, AmmError::PairInUse);
+
+    test.seed = 42;
+    let lp = test.balance(payer, test.mint_lp()).await;
+    test.withdraw(lp).await.unwrap();
+    test.close_pool().await.unwrap();
+
+    test.seed = 43;
+    test.contest_pair(registered).await.unwrap();
+    assert_eq!(test.fetch_pair().await.config, test.config());
+}
@@ -0,0 +1,329 @@
+mod common;
+
+use amm::{
+    helpers::sqrt_u128,
+    quote::{Pool, SwapQuote},
+    Config, CurveType, Observation, Oracle, MINIMUM_LIQUIDITY, OBSERVATIONS, VOLATILITY_DECAY,
+};
+use common::{config, Rng};
+use constant_product_curve::{ConstantProduct, LiquidityPair};
+
+const ROUNDS: usize = 256;
+const STEPS: usize = 16;
+const ONE: u128 = 1 << 64;
+
+fn pool(config: &Config, vault_x: u64, vault_y: u64, lp_supply: u64) -> Pool<'_> {
+    Pool {
+        config,
+        vault_x,
+        vault_y,
+        lp_supply,
+        now: 0,
+    }
+}
+
+#[test]
+fn swap_quote_matches_the_constant_product_curve() {
+    let mut rng = Rng(0x2545_f491_4f6c_dd1d);
+
+    for _ in 0..ROUNDS * STEPS {
+        let x = rng.range(1_000_000, 1_000_000_000_000);
+        let y = rng.range(1_000_000, 1_000_000_000_000);
+        let fee = rng.range(0, 1_000) as u16;
+        let amount = rng.range(1, x / 2);
+        let config = config(CurveType::ConstantProduct, 0, fee);
+
+        let quote = pool(&config, x, y, x).swap(true, amount).unwrap();
+        let res = ConstantProduct::init(x, y, x, fee, None)
+            .unwrap()
+            .swap(LiquidityPair::X, amount, 0)
+            .unwrap();
+        assert_eq!(quote.amount_in, res.deposit);
+        assert_eq!(quote.amount_out, res.withdraw);
+        assert_eq!(quote.fee, res.fee);
+    }
+}
+
+#[test]
+fn quotes_leave_protocol_fees_out_of_the_reserves() {
+    let mut rng = Rng(0x9e37_79b9_7f4a_7c15);
+
+    for _ in 0..ROUNDS * STEPS {
+        let x = rng.range(1_000_000, 1_000_000_000_000);
+        let y = rng.range(1_000_000, 1_000_000_000_000);
+        let (fees_x, fees_y) = (rng.range(0, x / 10), rng.range(0, y / 10));
+        let amount = rng.range(1, x / 2);
+
+        let mut with_fees = config(CurveType::ConstantProduct, 0, 30);
+        with_fees.protocol_fees_x = fees_x;
+        with_fees.protocol_fees_y = fees_y;
+        let without_fees = config(CurveType::ConstantProduct, 0, 30);
+
+        assert_eq!(
+            pool(&with_fees, x + fees_x, y + fees_y, x)
+                .swap(true, amount)
+                .unwrap(),
+            pool(&without_fees, x, y, x).swap(true, amount).unwrap()
+        );
+        assert_eq!(
+            pool(&with_fees, x + fees_x, y + fees_y, x)
+                .withdraw(x / 2)
+                .unwrap(),
+            pool(&without_fees, x, y, x).withdraw(x / 2).unwrap()
+        );
+    }
+}
+
+#[test]
+fn exact_out_quote_covers_the_requested_output_on_both_curves() {
+    let mut rng = Rng(0xd1b5_4a32_d192_ed03);
+
+    for _ in 0..ROUNDS * STEPS {
+        let x = rng.range(1_000_000, 1_000_000_000_000);
+        let y = rng.range(1_000_000, 1_000_000_000_000);
+        let fee = rng.range(0, 1_000) as u16;
+        let amount_out = rng.range(1, y / 2);
+
+        for config in [
+            config(CurveType::ConstantProduct, 0, fee),
+            config(CurveType::StableSwap, rng.range(1, 10_000), fee),
+        ] {
+            let pool = pool(&config, x, y, x);
+            let quote = pool.swap_exact_out(true, amount_out).unwrap();
+            assert_eq!(quote.amount_out, amount_out);
+
+            // Feeding the quoted input to `swap` pays out at least as much
+            let SwapQuote {
+                amount_out: out, ..
+            } = pool.swap(true, quote.amount_in).unwrap();
+            assert!(out >= amount_out);
+        }
+    }
+}
+
+#[test]
+fn price_impact_grows_with_trade_size() {
+    let mut rng = Rng(0x94d0_49bb_1331_11eb);
+
+    for _ in 0..ROUNDS {
+        let x = rng.range(1_000_000_000, 1_000_000_000_000);
+        let y = rng.range(1_000_000_000, 1_000_000_000_000);
+        let fee = rng.range(0, 1_000) as u16;
+
+        for config in [
+            config(CurveType::ConstantProduct, 0, fee),
+            config(CurveType::StableSwap, rng.range(1, 1_000), fee),
+        ] {
+            let pool = pool(&config, x, y, x);
+            let mut last = 0;
+            for step in 1..=STEPS as u64 {
+                let quote = pool.swap(true, x / 32 * step).unwrap();
+                let price_impact = pool.price_impact(true, &quote).unwrap();
+                assert!(price_impact >= last);
+                last = price_impact;
+            }
+            assert!(last > 0);
+        }
+    }
+}
+
+#[test]
+fn spot_price_follows_the_curve() {
+    let config_cp = config(CurveType::ConstantProduct, 0, 0);
+    let price = pool(&config_cp, 1_000_000, 4_000_000, 1)
+        .spot_price(true)
+        .unwrap();
+    assert_eq!(price, 4 * ONE);
+
+    // A balanced StableSwap pool trades at par, and stays close to it when slightly off balance
+    let config_stable = config(CurveType::StableSwap, 100, 0);
+    let price = pool(&config_stable, 1_000_000_000, 1_000_000_000, 1)
+        .spot_price(true)
+        .unwrap();
+    assert!(price.abs_diff(ONE) <= ONE / 1_000_000);
+
+    let cp = pool(&config_cp, 1_000_000_000, 1_100_000_000, 1)
+        .spot_price(true)
+        .unwrap();
+    let stable = pool(&config_stable, 1_000_000_000, 1_100_000_000, 1)
+        .spot_price(true)
+        .unwrap();
+    assert!(stable > ONE && stable < cp);
+}
+
+#[test]
+fn oracle_prices_follow_the_curve() {
+    let config_stable = config(CurveType::StableSwap, 100, 0);
+    let stable = pool(&config_stable, 1_000_000_000, 1_100_000_000, 1);
+    assert_eq!(
+        stable.oracle_prices().unwrap(),
+        Some((
+            stable.spot_price(true).unwrap(),
+            stable.spot_price(false).unwrap()
+        ))
+    );
+    assert_eq!(
+        pool(&config_stable, 0, 1_000, 0).oracle_prices().unwrap(),
+        None
+    );
+}
+
+#[test]
+fn oracle_only_advances_when_a_price_is_added() {
+    let mut oracle = Oracle {
+        config: Default::default(),
+        price_x_cumulative: 0,
+        price_y_cumulative: 0,
+        last_update: 0,
+        index: 0,
+        observations: [Observation::default(); OBSERVATIONS],
+        bump: 0,
+    };
+
+    oracle.update(Some((ONE, 2 * ONE)), 10);
+    assert_eq!(oracle.last_update, 10);
+    assert_eq!(oracle.price_x_cumulative, 10 * ONE);
+
+    // An empty pool has no price, so the gap is left for the next one
+    oracle.update(None, 20);
+    assert_eq!(oracle.last_update, 10);
+    assert_eq!(oracle.price_x_cumulative, 10 * ONE);
+
+    oracle.update(Some((3 * ONE, ONE)), 30);
+    assert_eq!(oracle.last_update, 30);
+    assert_eq!(oracle.price_x_cumulative, 70 * ONE);
+    assert_eq!(oracle.price_y_cumulative, 40 * ONE);
+}
+
+#[test]
+fn first_deposit_quote_locks_the_minimum_liquidity() {
+    let mut rng = Rng(0xbf58_476d_1ce4_e5b9);
+
+    for _ in 0..ROUNDS * STEPS {
+        let max_x = rng.range(1_000_000, 1_000_000_000_000);
+        let max_y = rng.range(1_000_000, 1_000_000_000_000);
+        let config = config(CurveType::ConstantProduct, 0, 30);
+
+        let quote = pool(&config, 0, 0, 0).deposit(1, max_x, max_y).unwrap();
+        assert_eq!((quote.amount_x, quote.amount_y), (max_x, max_y));
+        assert_eq!(
+            quote.lp_amount as u128 + MINIMUM_LIQUIDITY as u128,
+            sqrt_u128(max_x as u128 * max_y as u128)
+        );
+    }
+
+    let config = config(CurveType::ConstantProduct, 0, 30);
+    assert!(pool(&config, 0, 0, 0).deposit(1, 1_000, 1_000).is_err());
+}
+
+#[test]
+fn first_deposit_quote_counts_donations() {
+    let mut rng = Rng(0x94d0_49bb_1331_11eb);
+
+    for _ in 0..ROUNDS * STEPS {
+        let (donated_x, donated_y) = (rng.range(0, 1_000_000), rng.range(0, 1_000_000));
+        let max_x = rng.range(1_000_000, 1_000_000_000_000);
+        let max_y = rng.range(1_000_000, 1_000_000_000_000);
+        let config = config(CurveType::ConstantProduct, 0, 30);
+
+        let quote = pool(&config, donated_x, donated_y, 0)
+            .deposit(1, max_x, max_y)
+            .unwrap();
+        assert_eq!((quote.amount_x, quote.amount_y), (max_x, max_y));
+        assert_eq!(
+            quote.lp_amount as u128 + MINIMUM_LIQUIDITY as u128,
+            sqrt_u128((donated_x + max_x) as u128 * (donated_y + max_y) as u128)
+        );
+    }
+
+    // A one-sided donation still lets the first deposit through
+    let config = config(CurveType::ConstantProduct, 0, 30);
+    assert!(pool(&config, 1_000, 0, 0)
+        .deposit(1, 1_000_000, 1_000_000)
+        .is_ok());
+}
+
+#[test]
+fn deposit_then_withdraw_never_returns_more() {
+    let mut rng = Rng(0xc2b2_ae3d_27d4_eb4f);
+
+    for _ in 0..ROUNDS * STEPS {
+        let x = rng.range(1_000_000, 1_000_000_000_000);
+        let y = rng.range(1_000_000, 1_000_000_000_000);
+        let l = rng.range(1_000_000, 1_000_000_000_000);
+        let amount = rng.range(1, l);
+        let config = config(CurveType::ConstantProduct, 0, 30);
+
+        let deposit = pool(&config, x, y, l).deposit(amount, 0, 0).unwrap();
+        let withdraw = pool(
+            &config,
+            x + deposit.amount_x,
+            y + deposit.amount_y,
+            l + deposit.lp_amount,
+        )
+        .withdraw(deposit.lp_amount)
+        .unwrap();
+        assert!(withdraw.amount_x <= deposit.amount_x);
+        assert!(withdraw.amount_y <= deposit.amount_y);
+    }
+}
+
+#[test]
+fn dynamic_fee_follows_recent_price_moves() {
+    let mut config = config(CurveType::ConstantProduct, 0, 30);
+    config.dynamic_fee = true;
+    config.min_fee = 10;
+    config.max_fee = 100;
+
+    // Calm pools charge the minimum, a move raises the fee until it fades out
+    assert_eq!(config.effective_fee(0), 10);
+    config.update_volatility(500, 0);
+    assert_eq!(config.effective_fee(0), 60);
+    assert_eq!(config.effective_fee(VOLATILITY_DECAY / 2), 35);
+    assert_eq!(config.effective_fee(VOLATILITY_DECAY), 10);
+
+    config.update_volatility(10_000, 0);
+    assert_eq!(config.effective_fee(0), 100);
+
+    // Without the dynamic fee the fixed fee applies and moves aren't tracked
+    config.dynamic_fee = false;
+    config.update_volatility(500, 0);
+    assert_eq!(config.effective_fee(0), 30);
+}
+
+#[test]
+fn swap_quote_charges_the_dynamic_fee() {
+    let mut rng = Rng(0x1f83_d9ab_fb41_bd6b);
+
+    for _ in 0..ROUNDS * STEPS {
+        let x = rng.range(1_000_000, 1_000_000_000_000);
+        let y = rng.range(1_000_000, 1_000_000_000_000);
+        let amount = rng.range(1, x / 2);
+        let mut config = config(CurveType::ConstantProduct, 0, 30);
+        config.dynamic_fee = true;
+        config.min_fee = rng.range(0, 100) as u16;
+        config.max_fee = config.min_fee + rng.range(0, 900) as u16;
+        config.volatility = rng.range(0, 20_000);
+
+        let fee = config.effective_fee(0);
+        assert!((config.min_fee..=config.max_fee).contains(&fee));
+
+        let current = pool(&config, x, y, x);
+        let quote = current.swap(true, amount).unwrap();
+        let res = ConstantProduct::init(x, y, x, fee, None)
+            .unwrap()
+            .swap(LiquidityPair::X, amount, 0)
+            .unwrap();
+        assert_eq!((quote.amount_out, quote.fee), (res.withdraw, res.fee));
+
+        // The price of X only falls when X is sold
+        let price_move = current
+            .price_move(true, quote.amount_in, quote.amount_out)
+            .unwrap();
+        let after = pool(&config, x + quote.amount_in, y - quote.amount_out, x)
+            .spot_price(true)
+            .unwrap();
+        let before = current.spot_price(true).unwrap();
+        assert_eq!(price_move as u128, (before - after) * 10000 / before,);
+    }
+}
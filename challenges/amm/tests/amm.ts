@@ -2,18 +2,31 @@ import * as anchor from "@coral-xyz/anchor";
 import { BN, Program, web3 } from "@coral-xyz/anchor";
 import { Amm } from "../target/types/amm";
 import { randomBytes } from "crypto";
+import { assert } from "chai";
 import {
   createMint,
   getOrCreateAssociatedTokenAccount,
   mintTo,
   Account,
+  getAccount,
+  getMint,
   ASSOCIATED_TOKEN_PROGRAM_ID,
   TOKEN_PROGRAM_ID,
   getAssociatedTokenAddressSync,
+  TOKEN_2022_PROGRAM_ID,
+  ExtensionType,
+  getMintLen,
+  createInitializeMintInstruction,
+  createInitializeTransferFeeConfigInstruction,
+  createInitializeNonTransferableMintInstruction,
 } from "@solana/spl-token";
 
 const commitment: web3.Commitment = "confirmed";
 
+// Curve types accepted by `initialize`
+const constantProduct = { constantProduct: {} };
+const stableSwap = { stableSwap: {} };
+
 // Helpers
 const confirmTx = async (signature: string) => {
   console.log("Confirming tx: ", signature);
@@ -45,16 +58,25 @@ describe("amm", () => {
     web3.Keypair.generate(),
   ];
 
-  // Random seed
+  // Random seeds, one per pool
   const seed = new BN(randomBytes(8));
+  const seed2 = new BN(randomBytes(8));
 
   // PDAs
+  const [config] = web3.PublicKey.findProgramAddressSync(
+    [Buffer.from("config"), seed.toBuffer().reverse()],
+    program.programId
+  );
   const [auth] = web3.PublicKey.findProgramAddressSync(
-    [Buffer.from("auth")],
+    [Buffer.from("auth"), config.toBuffer()],
     program.programId
   );
-  const [config] = web3.PublicKey.findProgramAddressSync(
-    [Buffer.from("config"), seed.toBuffer().reverse()],
+  const [config2] = web3.PublicKey.findProgramAddressSync(
+    [Buffer.from("config"), seed2.toBuffer().reverse()],
+    program.programId
+  );
+  const [auth2] = web3.PublicKey.findProgramAddressSync(
+    [Buffer.from("auth"), config2.toBuffer()],
     program.programId
   );
 
@@ -65,6 +87,30 @@ describe("amm", () => {
     [Buffer.from("lp"), config.toBuffer()],
     program.programId
   );
+  let [mint_lp2] = web3.PublicKey.findProgramAddressSync(
+    [Buffer.from("lp"), config2.toBuffer()],
+    program.programId
+  );
+
+  // Oracles
+  const [oracle] = web3.PublicKey.findProgramAddressSync(
+    [Buffer.from("oracle"), config.toBuffer()],
+    program.programId
+  );
+  const [oracle2] = web3.PublicKey.findProgramAddressSync(
+    [Buffer.from("oracle"), config2.toBuffer()],
+    program.programId
+  );
+
+  // Stats
+  const [stats] = web3.PublicKey.findProgramAddressSync(
+    [Buffer.from("stats"), config.toBuffer()],
+    program.programId
+  );
+  const [stats2] = web3.PublicKey.findProgramAddressSync(
+    [Buffer.from("stats"), config2.toBuffer()],
+    program.programId
+  );
 
   // ATAs
   let initializer_x_ata: Account;
@@ -79,6 +125,11 @@ describe("amm", () => {
 
   let vault_lp_ata: web3.PublicKey;
 
+  let initializer_lp2_ata: web3.PublicKey;
+  let vault_x2_ata: web3.PublicKey;
+  let vault_y2_ata: web3.PublicKey;
+  let vault_lp2_ata: web3.PublicKey;
+
   it("Airdrop", async () => {
     await Promise.all(
       [initializer, user].map(async (k) => {
@@ -135,6 +186,14 @@ describe("amm", () => {
 
     vault_lp_ata = getAssociatedTokenAddressSync(mint_lp, auth, true);
 
+    initializer_lp2_ata = getAssociatedTokenAddressSync(
+      mint_lp2,
+      initializer.publicKey
+    );
+    vault_x2_ata = getAssociatedTokenAddressSync(mint_x, auth2, true);
+    vault_y2_ata = getAssociatedTokenAddressSync(mint_y, auth2, true);
+    vault_lp2_ata = getAssociatedTokenAddressSync(mint_lp2, auth2, true);
+
     await mintTo(
       connection,
       user,
@@ -156,7 +215,15 @@ describe("amm", () => {
   it("Initialize", async () => {
     // Add your test here.
     const tx = await program.methods
-      .initialize(seed, 300, initializer.publicKey)
+      .initialize(
+        seed,
+        300,
+        initializer.publicKey,
+        constantProduct,
+        new BN(0),
+        6,
+        false
+      )
       .accounts({
         initializer: initializer.publicKey,
         mintX: mint_x,
@@ -164,8 +231,11 @@ describe("amm", () => {
         mintLp: mint_lp,
         vaultX: vault_x_ata,
         vaultY: vault_y_ata,
+        vaultLp: vault_lp_ata,
         auth,
         config,
+        oracle,
+        stats,
         associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
         tokenProgram: TOKEN_PROGRAM_ID,
         systemProgram: web3.SystemProgram.programId,
@@ -190,11 +260,14 @@ describe("amm", () => {
         mintLp: mint_lp,
         vaultX: vault_x_ata,
         vaultY: vault_y_ata,
+        vaultLp: vault_lp_ata,
         userX: initializer_x_ata.address,
         userY: initializer_y_ata.address,
         userLp: initializer_lp_ata,
         auth,
         config,
+        oracle,
+        stats,
         associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
         tokenProgram: TOKEN_PROGRAM_ID,
         systemProgram: web3.SystemProgram.programId,
@@ -224,6 +297,69 @@ describe("amm", () => {
         userLp: initializer_lp_ata,
         auth,
         config,
+        oracle,
+        stats,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: web3.SystemProgram.programId,
+      })
+      .signers([initializer])
+      .rpc();
+    confirmTx(tx);
+  });
+
+  it("Withdraw", async () => {
+    const tx = await program.methods
+      .withdraw(
+        new BN(10 * 10 ** 6),
+        new BN(1),
+        new BN(1),
+        new BN(Math.floor(new Date().getTime() / 1000) + 600)
+      )
+      .accounts({
+        user: initializer.publicKey,
+        mintX: mint_x,
+        mintY: mint_y,
+        mintLp: mint_lp,
+        vaultX: vault_x_ata,
+        vaultY: vault_y_ata,
+        userX: initializer_x_ata.address,
+        userY: initializer_y_ata.address,
+        userLp: initializer_lp_ata,
+        auth,
+        config,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: web3.SystemProgram.programId,
+      })
+      .signers([initializer])
+      .rpc();
+    confirmTx(tx);
+  });
+
+  it("Initialize a second pool over the same mints", async () => {
+    const tx = await program.methods
+      .initialize(
+        seed2,
+        100,
+        initializer.publicKey,
+        constantProduct,
+        new BN(0),
+        6,
+        false
+      )
+      .accounts({
+        initializer: initializer.publicKey,
+        mintX: mint_x,
+        mintY: mint_y,
+        mintLp: mint_lp2,
+        vaultX: vault_x2_ata,
+        vaultY: vault_y2_ata,
+        vaultLp: vault_lp2_ata,
+        auth: auth2,
+        config: config2,
+        oracle: oracle2,
+        stats: stats2,
         associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
         tokenProgram: TOKEN_PROGRAM_ID,
         systemProgram: web3.SystemProgram.programId,
@@ -232,4 +368,2010 @@ describe("amm", () => {
       .rpc();
     confirmTx(tx);
   });
+
+  it("Deposit into the second pool", async () => {
+    const tx = await program.methods
+      .deposit(
+        new BN(7 * 10 ** 6),
+        new BN(7 * 10 ** 6),
+        new BN(11 * 10 ** 6),
+        new BN(Math.floor(new Date().getTime() / 1000) + 600)
+      )
+      .accounts({
+        user: initializer.publicKey,
+        mintX: mint_x,
+        mintY: mint_y,
+        mintLp: mint_lp2,
+        vaultX: vault_x2_ata,
+        vaultY: vault_y2_ata,
+        vaultLp: vault_lp2_ata,
+        userX: initializer_x_ata.address,
+        userY: initializer_y_ata.address,
+        userLp: initializer_lp2_ata,
+        auth: auth2,
+        config: config2,
+        oracle: oracle2,
+        stats: stats2,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: web3.SystemProgram.programId,
+      })
+      .signers([initializer])
+      .rpc();
+    confirmTx(tx);
+  });
+
+  it("Pools over the same mints keep isolated reserves", async () => {
+    assert.notEqual(auth.toBase58(), auth2.toBase58());
+    assert.notEqual(vault_x_ata.toBase58(), vault_x2_ata.toBase58());
+    assert.notEqual(vault_y_ata.toBase58(), vault_y2_ata.toBase58());
+
+    const vaultX2 = await getAccount(connection, vault_x2_ata, commitment);
+    const vaultY2 = await getAccount(connection, vault_y2_ata, commitment);
+    assert.equal(vaultX2.amount.toString(), (7 * 10 ** 6).toString());
+    assert.equal(vaultY2.amount.toString(), (11 * 10 ** 6).toString());
+
+    // Trading in the first pool must not move the second pool's reserves
+    const tx = await program.methods
+      .swap(
+        true,
+        new BN(1 * 10 ** 6),
+        new BN(1),
+        new BN(Math.floor(new Date().getTime() / 1000) + 600)
+      )
+      .accounts({
+        user: initializer.publicKey,
+        mintX: mint_x,
+        mintY: mint_y,
+        mintLp: mint_lp,
+        vaultX: vault_x_ata,
+        vaultY: vault_y_ata,
+        userX: initializer_x_ata.address,
+        userY: initializer_y_ata.address,
+        userLp: initializer_lp_ata,
+        auth,
+        config,
+        oracle,
+        stats,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: web3.SystemProgram.programId,
+      })
+      .signers([initializer])
+      .rpc();
+    await confirmTx(tx);
+
+    const vaultX2After = await getAccount(connection, vault_x2_ata, commitment);
+    const vaultY2After = await getAccount(connection, vault_y2_ata, commitment);
+    assert.equal(vaultX2After.amount.toString(), vaultX2.amount.toString());
+    assert.equal(vaultY2After.amount.toString(), vaultY2.amount.toString());
+  });
+
+  it("Lock pool", async () => {
+    const tx = await program.methods
+      .lock()
+      .accounts({ user: initializer.publicKey, config })
+      .signers([initializer])
+      .rpc();
+    await confirmTx(tx);
+  });
+
+  it("Swap fails while the pool is locked", async () => {
+    try {
+      await program.methods
+        .swap(
+          true,
+          new BN(1 * 10 ** 6),
+          new BN(1),
+          new BN(Math.floor(new Date().getTime() / 1000) + 600)
+        )
+        .accounts({
+          user: initializer.publicKey,
+          mintX: mint_x,
+          mintY: mint_y,
+          mintLp: mint_lp,
+          vaultX: vault_x_ata,
+          vaultY: vault_y_ata,
+          userX: initializer_x_ata.address,
+          userY: initializer_y_ata.address,
+          userLp: initializer_lp_ata,
+          auth,
+          config,
+          oracle,
+          stats,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: web3.SystemProgram.programId,
+        })
+        .signers([initializer])
+        .rpc();
+      assert.fail("swap should fail on a locked pool");
+    } catch (e) {
+      assert.equal(e.error.errorCode.code, "PoolLocked");
+    }
+  });
+
+  it("Lock fails for a non-authority", async () => {
+    try {
+      await program.methods
+        .lock()
+        .accounts({ user: user.publicKey, config })
+        .signers([user])
+        .rpc();
+      assert.fail("lock should fail without the update authority");
+    } catch (e) {
+      assert.equal(e.error.errorCode.code, "InvalidAuthority");
+    }
+  });
+
+  it("Unlock pool", async () => {
+    const tx = await program.methods
+      .unlock()
+      .accounts({ user: initializer.publicKey, config })
+      .signers([initializer])
+      .rpc();
+    await confirmTx(tx);
+  });
+
+  it("Update fee", async () => {
+    const tx = await program.methods
+      .updateFee(250)
+      .accounts({ user: initializer.publicKey, config })
+      .signers([initializer])
+      .rpc();
+    await confirmTx(tx);
+    const account = await program.account.config.fetch(config);
+    assert.equal(account.fee, 250);
+  });
+
+  it("Enable and disable the dynamic fee", async () => {
+    await program.methods
+      .enableDynamicFee(10, 100)
+      .accounts({ user: initializer.publicKey, config })
+      .signers([initializer])
+      .rpc()
+      .then(confirmTx);
+    let account = await program.account.config.fetch(config);
+    assert.isTrue(account.dynamicFee);
+    assert.equal(account.minFee, 10);
+    assert.equal(account.maxFee, 100);
+
+    await program.methods
+      .disableDynamicFee()
+      .accounts({ user: initializer.publicKey, config })
+      .signers([initializer])
+      .rpc()
+      .then(confirmTx);
+    account = await program.account.config.fetch(config);
+    assert.isFalse(account.dynamicFee);
+    assert.equal(account.fee, 250);
+  });
+
+  it("Dynamic fee bounds must be ordered", async () => {
+    try {
+      await program.methods
+        .enableDynamicFee(100, 10)
+        .accounts({ user: initializer.publicKey, config })
+        .signers([initializer])
+        .rpc();
+      assert.fail("min fee can't be above max fee");
+    } catch (e) {
+      assert.equal(e.error.errorCode.code, "InvalidFee");
+    }
+  });
+
+  it("Transfer and renounce authority", async () => {
+    await program.methods
+      .transferAuthority(user.publicKey)
+      .accounts({ user: initializer.publicKey, config: config2 })
+      .signers([initializer])
+      .rpc()
+      .then(confirmTx);
+    await program.methods
+      .renounceAuthority()
+      .accounts({ user: user.publicKey, config: config2 })
+      .signers([user])
+      .rpc()
+      .then(confirmTx);
+    const account = await program.account.config.fetch(config2);
+    assert.isNull(account.authority);
+  });
+
+  it("Accrue and collect protocol fees", async () => {
+    await program.methods
+      .updateProtocolFee(5000)
+      .accounts({ user: initializer.publicKey, config })
+      .signers([initializer])
+      .rpc()
+      .then(confirmTx);
+
+    await program.methods
+      .swap(
+        true,
+        new BN(1 * 10 ** 6),
+        new BN(1),
+        new BN(Math.floor(new Date().getTime() / 1000) + 600)
+      )
+      .accounts({
+        user: initializer.publicKey,
+        mintX: mint_x,
+        mintY: mint_y,
+        mintLp: mint_lp,
+        vaultX: vault_x_ata,
+        vaultY: vault_y_ata,
+        userX: initializer_x_ata.address,
+        userY: initializer_y_ata.address,
+        userLp: initializer_lp_ata,
+        auth,
+        config,
+        oracle,
+        stats,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: web3.SystemProgram.programId,
+      })
+      .signers([initializer])
+      .rpc()
+      .then(confirmTx);
+
+    let account = await program.account.config.fetch(config);
+    assert.isTrue(account.protocolFeesX.gtn(0));
+
+    await program.methods
+      .collectProtocolFees()
+      .accounts({
+        user: initializer.publicKey,
+        mintX: mint_x,
+        mintY: mint_y,
+        vaultX: vault_x_ata,
+        vaultY: vault_y_ata,
+        treasuryX: initializer_x_ata.address,
+        treasuryY: initializer_y_ata.address,
+        auth,
+        config,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .signers([initializer])
+      .rpc()
+      .then(confirmTx);
+
+    account = await program.account.config.fetch(config);
+    assert.isTrue(account.protocolFeesX.eqn(0));
+    assert.isTrue(account.protocolFeesY.eqn(0));
+  });
+
+  it("Swap Y for an exact amount of X", async () => {
+    const before = await getAccount(
+      connection,
+      initializer_x_ata.address,
+      commitment
+    );
+    const tx = await program.methods
+      .swapExactOut(
+        false,
+        new BN(1 * 10 ** 6),
+        new BN(5 * 10 ** 6),
+        new BN(Math.floor(new Date().getTime() / 1000) + 600)
+      )
+      .accounts({
+        user: initializer.publicKey,
+        mintX: mint_x,
+        mintY: mint_y,
+        mintLp: mint_lp,
+        vaultX: vault_x_ata,
+        vaultY: vault_y_ata,
+        userX: initializer_x_ata.address,
+        userY: initializer_y_ata.address,
+        userLp: initializer_lp_ata,
+        auth,
+        config,
+        oracle,
+        stats,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: web3.SystemProgram.programId,
+      })
+      .signers([initializer])
+      .rpc();
+    await confirmTx(tx);
+    const after = await getAccount(
+      connection,
+      initializer_x_ata.address,
+      commitment
+    );
+    assert.equal((after.amount - before.amount).toString(), "1000000");
+  });
+
+  it("Single-sided deposit of X", async () => {
+    const tx = await program.methods
+      .depositSingle(
+        true,
+        new BN(2 * 10 ** 6),
+        new BN(1),
+        new BN(Math.floor(new Date().getTime() / 1000) + 600)
+      )
+      .accounts({
+        user: initializer.publicKey,
+        mintX: mint_x,
+        mintY: mint_y,
+        mintLp: mint_lp,
+        vaultX: vault_x_ata,
+        vaultY: vault_y_ata,
+        vaultLp: vault_lp_ata,
+        userX: initializer_x_ata.address,
+        userY: initializer_y_ata.address,
+        userLp: initializer_lp_ata,
+        auth,
+        config,
+        oracle,
+        stats,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: web3.SystemProgram.programId,
+      })
+      .signers([initializer])
+      .rpc();
+    await confirmTx(tx);
+  });
+
+  it("Observe a TWAP from the oracle", async () => {
+    // Make sure the initial observation is at least one second old
+    await new Promise((resolve) => setTimeout(resolve, 2000));
+    const twap = await program.methods
+      .observe(1)
+      .accounts({
+        mintX: mint_x,
+        mintY: mint_y,
+        vaultX: vault_x_ata,
+        vaultY: vault_y_ata,
+        auth,
+        config,
+        oracle,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .view();
+    assert.isTrue(twap.window.gtn(0));
+    assert.isTrue(twap.priceX.gtn(0));
+    assert.isTrue(twap.priceY.gtn(0));
+  });
+
+  it("Swap emits a Swapped event", async () => {
+    let listener: number;
+    const event = new Promise<any>((resolve) => {
+      listener = program.addEventListener("Swapped", (e) => resolve(e));
+    });
+
+    await program.methods
+      .swap(
+        true,
+        new BN(1 * 10 ** 6),
+        new BN(1),
+        new BN(Math.floor(new Date().getTime() / 1000) + 600)
+      )
+      .accounts({
+        user: initializer.publicKey,
+        mintX: mint_x,
+        mintY: mint_y,
+        mintLp: mint_lp,
+        vaultX: vault_x_ata,
+        vaultY: vault_y_ata,
+        userX: initializer_x_ata.address,
+        userY: initializer_y_ata.address,
+        userLp: initializer_lp_ata,
+        auth,
+        config,
+        oracle,
+        stats,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: web3.SystemProgram.programId,
+      })
+      .signers([initializer])
+      .rpc()
+      .then(confirmTx);
+
+    const swapped = await event;
+    await program.removeEventListener(listener);
+    assert.equal(swapped.config.toBase58(), config.toBase58());
+    assert.equal(swapped.user.toBase58(), initializer.publicKey.toBase58());
+    assert.isTrue(swapped.amountIn.eqn(1 * 10 ** 6));
+    assert.isTrue(swapped.reserveXAfter.gt(swapped.reserveXBefore));
+    assert.isTrue(swapped.reserveYAfter.lt(swapped.reserveYBefore));
+  });
+
+  it("Stats count every swap on the pool", async () => {
+    const before = await program.account.stats.fetch(stats);
+
+    await program.methods
+      .swap(
+        true,
+        new BN(1 * 10 ** 6),
+        new BN(1),
+        new BN(Math.floor(new Date().getTime() / 1000) + 600)
+      )
+      .accounts({
+        user: initializer.publicKey,
+        mintX: mint_x,
+        mintY: mint_y,
+        mintLp: mint_lp,
+        vaultX: vault_x_ata,
+        vaultY: vault_y_ata,
+        userX: initializer_x_ata.address,
+        userY: initializer_y_ata.address,
+        userLp: initializer_lp_ata,
+        auth,
+        config,
+        oracle,
+        stats,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: web3.SystemProgram.programId,
+      })
+      .signers([initializer])
+      .rpc()
+      .then(confirmTx);
+
+    const after = await program.account.stats.fetch(stats);
+    assert.isTrue(after.config.equals(config));
+    assert.equal(after.swapCount.sub(before.swapCount).toNumber(), 1);
+    assert.equal(after.volumeX.sub(before.volumeX).toNumber(), 1 * 10 ** 6);
+    assert.isTrue(after.volumeY.gt(before.volumeY));
+    assert.isTrue(after.feesX.gt(before.feesX));
+    assert.isTrue(after.feesY.eq(before.feesY));
+    assert.isTrue(after.depositCount.gtn(0));
+    assert.isTrue(after.lastSwap.gtn(0));
+  });
+
+  it("First deposit locks the minimum liquidity", async () => {
+    const locked = await getAccount(connection, vault_lp_ata, commitment);
+    assert.equal(locked.amount.toString(), "1000");
+    const locked2 = await getAccount(connection, vault_lp2_ata, commitment);
+    assert.equal(locked2.amount.toString(), "1000");
+  });
+
+  it("Flash loan is repaid with a fee in the same transaction", async () => {
+    const accounts = {
+      user: initializer.publicKey,
+      mintX: mint_x,
+      mintY: mint_y,
+      vaultX: vault_x2_ata,
+      vaultY: vault_y2_ata,
+      userX: initializer_x_ata.address,
+      userY: initializer_y_ata.address,
+      auth: auth2,
+      config: config2,
+      instructions: web3.SYSVAR_INSTRUCTIONS_PUBKEY,
+      tokenProgram: TOKEN_PROGRAM_ID,
+    };
+    const before = await getAccount(connection, vault_x2_ata, commitment);
+
+    const tx = new web3.Transaction().add(
+      await program.methods
+        .flashLoan(true, new BN(1 * 10 ** 6))
+        .accounts(accounts)
+        .instruction(),
+      await program.methods.flashRepay().accounts(accounts).instruction()
+    );
+    await web3.sendAndConfirmTransaction(connection, tx, [initializer]);
+
+    // 9 bps on 1 token
+    const after = await getAccount(connection, vault_x2_ata, commitment);
+    assert.equal((after.amount - before.amount).toString(), "900");
+
+    const pool = await program.account.config.fetch(config2);
+    assert.isFalse(pool.locked);
+  });
+
+  it("Flash loan fails without a repay", async () => {
+    try {
+      await program.methods
+        .flashLoan(true, new BN(1 * 10 ** 6))
+        .accounts({
+          user: initializer.publicKey,
+          mintX: mint_x,
+          mintY: mint_y,
+          vaultX: vault_x2_ata,
+          vaultY: vault_y2_ata,
+          userX: initializer_x_ata.address,
+          userY: initializer_y_ata.address,
+          auth: auth2,
+          config: config2,
+          instructions: web3.SYSVAR_INSTRUCTIONS_PUBKEY,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([initializer])
+        .rpc();
+      assert.fail("flash loan should need a repay");
+    } catch (e) {
+      assert.equal(e.error.errorCode.code, "FlashLoanNotRepaid");
+    }
+  });
+
+  it("Pool can't be unlocked while a flash loan is out", async () => {
+    const accounts = {
+      user: initializer.publicKey,
+      mintX: mint_x,
+      mintY: mint_y,
+      vaultX: vault_x2_ata,
+      vaultY: vault_y2_ata,
+      userX: initializer_x_ata.address,
+      userY: initializer_y_ata.address,
+      auth: auth2,
+      config: config2,
+      instructions: web3.SYSVAR_INSTRUCTIONS_PUBKEY,
+      tokenProgram: TOKEN_PROGRAM_ID,
+    };
+    const tx = new web3.Transaction().add(
+      await program.methods
+        .flashLoan(true, new BN(1 * 10 ** 6))
+        .accounts(accounts)
+        .instruction(),
+      await program.methods
+        .unlock()
+        .accounts({ user: initializer.publicKey, config: config2 })
+        .instruction(),
+      await program.methods.flashRepay().accounts(accounts).instruction()
+    );
+    try {
+      await web3.sendAndConfirmTransaction(connection, tx, [initializer]);
+      assert.fail("unlock should fail with a flash loan out");
+    } catch (e) {
+      assert.isTrue(e.logs.some((l) => l.includes("FlashLoanNotRepaid")));
+    }
+  });
+
+  it("Protocol fees can't be collected while a flash loan is out", async () => {
+    const accounts = {
+      user: initializer.publicKey,
+      mintX: mint_x,
+      mintY: mint_y,
+      vaultX: vault_x2_ata,
+      vaultY: vault_y2_ata,
+      userX: initializer_x_ata.address,
+      userY: initializer_y_ata.address,
+      auth: auth2,
+      config: config2,
+      instructions: web3.SYSVAR_INSTRUCTIONS_PUBKEY,
+      tokenProgram: TOKEN_PROGRAM_ID,
+    };
+    const tx = new web3.Transaction().add(
+      await program.methods
+        .flashLoan(true, new BN(1 * 10 ** 6))
+        .accounts(accounts)
+        .instruction(),
+      await program.methods
+        .collectProtocolFees()
+        .accounts({
+          user: initializer.publicKey,
+          mintX: mint_x,
+          mintY: mint_y,
+          vaultX: vault_x2_ata,
+          vaultY: vault_y2_ata,
+          treasuryX: initializer_x_ata.address,
+          treasuryY: initializer_y_ata.address,
+          auth: auth2,
+          config: config2,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .instruction(),
+      await program.methods.flashRepay().accounts(accounts).instruction()
+    );
+    try {
+      await web3.sendAndConfirmTransaction(connection, tx, [initializer]);
+      assert.fail("collect should fail with a flash loan out");
+    } catch (e) {
+      assert.isTrue(e.logs.some((l) => l.includes("FlashLoanNotRepaid")));
+    }
+  });
+
+  describe("route_swap", () => {
+    const seed3 = new BN(randomBytes(8));
+    const [config3] = web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("config"), seed3.toBuffer().reverse()],
+      program.programId
+    );
+    const [auth3] = web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("auth"), config3.toBuffer()],
+      program.programId
+    );
+    const [oracle3] = web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("oracle"), config3.toBuffer()],
+      program.programId
+    );
+    const [stats3] = web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("stats"), config3.toBuffer()],
+      program.programId
+    );
+    const [mint_lp3] = web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("lp"), config3.toBuffer()],
+      program.programId
+    );
+
+    let mint_z: web3.PublicKey;
+    let initializer_z_ata: Account;
+    let vault_x3_ata: web3.PublicKey;
+    let vault_z3_ata: web3.PublicKey;
+
+    it("Create an X/Z pool", async () => {
+      mint_z = await createMint(connection, user, user.publicKey, null, 6);
+      initializer_z_ata = await getOrCreateAssociatedTokenAccount(
+        connection,
+        initializer,
+        mint_z,
+        initializer.publicKey
+      );
+      await mintTo(
+        connection,
+        user,
+        mint_z,
+        initializer_z_ata.address,
+        user.publicKey,
+        21e8
+      ).then(confirmTx);
+
+      vault_x3_ata = getAssociatedTokenAddressSync(mint_x, auth3, true);
+      vault_z3_ata = getAssociatedTokenAddressSync(mint_z, auth3, true);
+      const vault_lp3_ata = getAssociatedTokenAddressSync(
+        mint_lp3,
+        auth3,
+        true
+      );
+
+      await program.methods
+        .initialize(
+          seed3,
+          30,
+          initializer.publicKey,
+          constantProduct,
+          new BN(0),
+          6,
+          false
+        )
+        .accounts({
+          initializer: initializer.publicKey,
+          mintX: mint_x,
+          mintY: mint_z,
+          mintLp: mint_lp3,
+          vaultX: vault_x3_ata,
+          vaultY: vault_z3_ata,
+          vaultLp: vault_lp3_ata,
+          auth: auth3,
+          config: config3,
+          oracle: oracle3,
+          stats: stats3,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: web3.SystemProgram.programId,
+        })
+        .signers([initializer])
+        .rpc()
+        .then(confirmTx);
+
+      await program.methods
+        .deposit(
+          new BN(10 * 10 ** 6),
+          new BN(10 * 10 ** 6),
+          new BN(10 * 10 ** 6),
+          new BN(Math.floor(new Date().getTime() / 1000) + 600)
+        )
+        .accounts({
+          user: initializer.publicKey,
+          mintX: mint_x,
+          mintY: mint_z,
+          mintLp: mint_lp3,
+          vaultX: vault_x3_ata,
+          vaultY: vault_z3_ata,
+          vaultLp: vault_lp3_ata,
+          userX: initializer_x_ata.address,
+          userY: initializer_z_ata.address,
+          userLp: getAssociatedTokenAddressSync(
+            mint_lp3,
+            initializer.publicKey
+          ),
+          auth: auth3,
+          config: config3,
+          oracle: oracle3,
+          stats: stats3,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: web3.SystemProgram.programId,
+        })
+        .signers([initializer])
+        .rpc()
+        .then(confirmTx);
+    });
+
+    it("Route Y -> X -> Z", async () => {
+      const hop = (
+        config: web3.PublicKey,
+        auth: web3.PublicKey,
+        oracle: web3.PublicKey,
+        stats: web3.PublicKey,
+        mintLp: web3.PublicKey,
+        vaultX: web3.PublicKey,
+        vaultY: web3.PublicKey,
+        mintX: web3.PublicKey,
+        mintY: web3.PublicKey
+      ) => [
+        { pubkey: config, isSigner: false, isWritable: true },
+        { pubkey: auth, isSigner: false, isWritable: false },
+        { pubkey: oracle, isSigner: false, isWritable: true },
+        { pubkey: stats, isSigner: false, isWritable: true },
+        { pubkey: mintLp, isSigner: false, isWritable: false },
+        { pubkey: vaultX, isSigner: false, isWritable: true },
+        { pubkey: vaultY, isSigner: false, isWritable: true },
+        { pubkey: mintX, isSigner: false, isWritable: false },
+        { pubkey: mintY, isSigner: false, isWritable: false },
+      ];
+
+      const before = await getAccount(
+        connection,
+        initializer_z_ata.address,
+        commitment
+      );
+
+      await program.methods
+        .routeSwap(
+          2,
+          new BN(1 * 10 ** 6),
+          new BN(1),
+          new BN(Math.floor(new Date().getTime() / 1000) + 600)
+        )
+        .accounts({
+          user: initializer.publicKey,
+          userIn: initializer_y_ata.address,
+          userOut: initializer_z_ata.address,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .remainingAccounts([
+          ...hop(
+            config,
+            auth,
+            oracle,
+            stats,
+            mint_lp,
+            vault_x_ata,
+            vault_y_ata,
+            mint_x,
+            mint_y
+          ),
+          ...hop(
+            config3,
+            auth3,
+            oracle3,
+            stats3,
+            mint_lp3,
+            vault_x3_ata,
+            vault_z3_ata,
+            mint_x,
+            mint_z
+          ),
+        ])
+        .signers([initializer])
+        .rpc()
+        .then(confirmTx);
+
+      const after = await getAccount(
+        connection,
+        initializer_z_ata.address,
+        commitment
+      );
+      assert.isTrue(after.amount > before.amount);
+
+      // The second hop is the only swap the X/Z pool has seen
+      const hopStats = await program.account.stats.fetch(stats3);
+      assert.equal(hopStats.swapCount.toNumber(), 1);
+    });
+  });
+
+  describe("token-2022 transfer fees", () => {
+    const seed4 = new BN(randomBytes(8));
+    const [config4] = web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("config"), seed4.toBuffer().reverse()],
+      program.programId
+    );
+    const [auth4] = web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("auth"), config4.toBuffer()],
+      program.programId
+    );
+    const [oracle4] = web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("oracle"), config4.toBuffer()],
+      program.programId
+    );
+    const [stats4] = web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("stats"), config4.toBuffer()],
+      program.programId
+    );
+    const [mint_lp4] = web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("lp"), config4.toBuffer()],
+      program.programId
+    );
+
+    // 1% transfer fee on X
+    const feeBps = 100;
+
+    let mint_fee: web3.PublicKey;
+    let mint_plain: web3.PublicKey;
+    let initializer_fee_ata: Account;
+    let initializer_plain_ata: Account;
+    let vault_fee_ata: web3.PublicKey;
+    let vault_plain_ata: web3.PublicKey;
+    let vault_lp4_ata: web3.PublicKey;
+
+    const ata = (mint: web3.PublicKey, owner: web3.PublicKey) =>
+      getAssociatedTokenAddressSync(mint, owner, true, TOKEN_2022_PROGRAM_ID);
+
+    const createToken2022Mint = async (
+      extensions: ExtensionType[],
+      initExtensions: (mint: web3.PublicKey) => web3.TransactionInstruction[]
+    ) => {
+      const mint = web3.Keypair.generate();
+      const space = getMintLen(extensions);
+      const lamports = await connection.getMinimumBalanceForRentExemption(
+        space
+      );
+      const tx = new web3.Transaction().add(
+        web3.SystemProgram.createAccount({
+          fromPubkey: user.publicKey,
+          newAccountPubkey: mint.publicKey,
+          space,
+          lamports,
+          programId: TOKEN_2022_PROGRAM_ID,
+        }),
+        ...initExtensions(mint.publicKey),
+        createInitializeMintInstruction(
+          mint.publicKey,
+          6,
+          user.publicKey,
+          null,
+          TOKEN_2022_PROGRAM_ID
+        )
+      );
+      await web3.sendAndConfirmTransaction(connection, tx, [user, mint]);
+      return mint.publicKey;
+    };
+
+    const initializeAccounts = (
+      mintX: web3.PublicKey,
+      mintY: web3.PublicKey
+    ) => ({
+      initializer: initializer.publicKey,
+      mintX,
+      mintY,
+      mintLp: mint_lp4,
+      vaultX: ata(mintX, auth4),
+      vaultY: ata(mintY, auth4),
+      vaultLp: ata(mint_lp4, auth4),
+      auth: auth4,
+      config: config4,
+      oracle: oracle4,
+      stats: stats4,
+      associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+      tokenProgram: TOKEN_2022_PROGRAM_ID,
+      systemProgram: web3.SystemProgram.programId,
+    });
+
+    it("Create a transfer-fee pool", async () => {
+      mint_fee = await createToken2022Mint(
+        [ExtensionType.TransferFeeConfig],
+        (mint) => [
+          createInitializeTransferFeeConfigInstruction(
+            mint,
+            user.publicKey,
+            user.publicKey,
+            feeBps,
+            BigInt(10 ** 9),
+            TOKEN_2022_PROGRAM_ID
+          ),
+        ]
+      );
+      mint_plain = await createToken2022Mint([], () => []);
+
+      initializer_fee_ata = await getOrCreateAssociatedTokenAccount(
+        connection,
+        initializer,
+        mint_fee,
+        initializer.publicKey,
+        false,
+        commitment,
+        undefined,
+        TOKEN_2022_PROGRAM_ID
+      );
+      initializer_plain_ata = await getOrCreateAssociatedTokenAccount(
+        connection,
+        initializer,
+        mint_plain,
+        initializer.publicKey,
+        false,
+        commitment,
+        undefined,
+        TOKEN_2022_PROGRAM_ID
+      );
+      for (const [mint, account] of [
+        [mint_fee, initializer_fee_ata.address],
+        [mint_plain, initializer_plain_ata.address],
+      ]) {
+        await mintTo(
+          connection,
+          user,
+          mint,
+          account,
+          user.publicKey,
+          100e6,
+          [],
+          undefined,
+          TOKEN_2022_PROGRAM_ID
+        ).then(confirmTx);
+      }
+
+      vault_fee_ata = ata(mint_fee, auth4);
+      vault_plain_ata = ata(mint_plain, auth4);
+      vault_lp4_ata = ata(mint_lp4, auth4);
+
+      await program.methods
+        .initialize(
+          seed4,
+          30,
+          initializer.publicKey,
+          constantProduct,
+          new BN(0),
+          6,
+          false
+        )
+        .accounts(initializeAccounts(mint_fee, mint_plain))
+        .signers([initializer])
+        .rpc()
+        .then(confirmTx);
+
+      await program.methods
+        .deposit(
+          new BN(1),
+          new BN(20 * 10 ** 6),
+          new BN(20 * 10 ** 6),
+          new BN(Math.floor(new Date().getTime() / 1000) + 600)
+        )
+        .accounts({
+          user: initializer.publicKey,
+          mintX: mint_fee,
+          mintY: mint_plain,
+          mintLp: mint_lp4,
+          vaultX: vault_fee_ata,
+          vaultY: vault_plain_ata,
+          vaultLp: vault_lp4_ata,
+          userX: initializer_fee_ata.address,
+          userY: initializer_plain_ata.address,
+          userLp: ata(mint_lp4, initializer.publicKey),
+          auth: auth4,
+          config: config4,
+          oracle: oracle4,
+          stats: stats4,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          tokenProgram: TOKEN_2022_PROGRAM_ID,
+          systemProgram: web3.SystemProgram.programId,
+        })
+        .signers([initializer])
+        .rpc()
+        .then(confirmTx);
+
+      // The vault only holds what is left after the transfer fee
+      const vault = await getAccount(
+        connection,
+        vault_fee_ata,
+        commitment,
+        TOKEN_2022_PROGRAM_ID
+      );
+      assert.equal(vault.amount.toString(), (19.8e6).toString());
+    });
+
+    it("Swap prices only what reaches the vault", async () => {
+      const before = await getAccount(
+        connection,
+        vault_fee_ata,
+        commitment,
+        TOKEN_2022_PROGRAM_ID
+      );
+
+      await program.methods
+        .swap(
+          true,
+          new BN(1 * 10 ** 6),
+          new BN(1),
+          new BN(Math.floor(new Date().getTime() / 1000) + 600)
+        )
+        .accounts({
+          user: initializer.publicKey,
+          mintX: mint_fee,
+          mintY: mint_plain,
+          mintLp: mint_lp4,
+          vaultX: vault_fee_ata,
+          vaultY: vault_plain_ata,
+          userX: initializer_fee_ata.address,
+          userY: initializer_plain_ata.address,
+          userLp: ata(mint_lp4, initializer.publicKey),
+          auth: auth4,
+          config: config4,
+          oracle: oracle4,
+          stats: stats4,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          tokenProgram: TOKEN_2022_PROGRAM_ID,
+          systemProgram: web3.SystemProgram.programId,
+        })
+        .signers([initializer])
+        .rpc()
+        .then(confirmTx);
+
+      const after = await getAccount(
+        connection,
+        vault_fee_ata,
+        commitment,
+        TOKEN_2022_PROGRAM_ID
+      );
+      assert.equal(
+        (after.amount - before.amount).toString(),
+        ((1e6 * (10000 - feeBps)) / 10000).toString()
+      );
+    });
+
+    it("Rejects a non-transferable mint", async () => {
+      const mint_soulbound = await createToken2022Mint(
+        [ExtensionType.NonTransferable],
+        (mint) => [
+          createInitializeNonTransferableMintInstruction(
+            mint,
+            TOKEN_2022_PROGRAM_ID
+          ),
+        ]
+      );
+
+      const seed5 = new BN(randomBytes(8));
+      const [config5] = web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("config"), seed5.toBuffer().reverse()],
+        program.programId
+      );
+      const [auth5] = web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("auth"), config5.toBuffer()],
+        program.programId
+      );
+      const [mint_lp5] = web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("lp"), config5.toBuffer()],
+        program.programId
+      );
+      const [oracle5] = web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("oracle"), config5.toBuffer()],
+        program.programId
+      );
+      const [stats5] = web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("stats"), config5.toBuffer()],
+        program.programId
+      );
+
+      try {
+        await program.methods
+          .initialize(
+            seed5,
+            30,
+            initializer.publicKey,
+            constantProduct,
+            new BN(0),
+            6,
+            false
+          )
+          .accounts({
+            ...initializeAccounts(mint_soulbound, mint_plain),
+            mintLp: mint_lp5,
+            vaultX: ata(mint_soulbound, auth5),
+            vaultY: ata(mint_plain, auth5),
+            vaultLp: ata(mint_lp5, auth5),
+            auth: auth5,
+            config: config5,
+            oracle: oracle5,
+            stats: stats5,
+          })
+          .signers([initializer])
+          .rpc();
+        assert.fail("initialize should have failed");
+      } catch (e) {
+        assert.equal(e.error.errorCode.code, "NonTransferableMint");
+      }
+    });
+  });
+
+  describe("stable swap", () => {
+    const seed6 = new BN(randomBytes(8));
+    const [config6] = web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("config"), seed6.toBuffer().reverse()],
+      program.programId
+    );
+    const [auth6] = web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("auth"), config6.toBuffer()],
+      program.programId
+    );
+    const [oracle6] = web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("oracle"), config6.toBuffer()],
+      program.programId
+    );
+    const [stats6] = web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("stats"), config6.toBuffer()],
+      program.programId
+    );
+    const [mint_lp6] = web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("lp"), config6.toBuffer()],
+      program.programId
+    );
+    const vault_x6_ata = getAssociatedTokenAddressSync(mint_x, auth6, true);
+    const vault_y6_ata = getAssociatedTokenAddressSync(mint_y, auth6, true);
+    const vault_lp6_ata = getAssociatedTokenAddressSync(mint_lp6, auth6, true);
+
+    const accounts = () => ({
+      user: initializer.publicKey,
+      mintX: mint_x,
+      mintY: mint_y,
+      mintLp: mint_lp6,
+      vaultX: vault_x6_ata,
+      vaultY: vault_y6_ata,
+      userX: initializer_x_ata.address,
+      userY: initializer_y_ata.address,
+      userLp: getAssociatedTokenAddressSync(mint_lp6, initializer.publicKey),
+      auth: auth6,
+      config: config6,
+      oracle: oracle6,
+      stats: stats6,
+      associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      systemProgram: web3.SystemProgram.programId,
+    });
+
+    it("Initialize a StableSwap pool", async () => {
+      await program.methods
+        .initialize(
+          seed6,
+          4,
+          initializer.publicKey,
+          stableSwap,
+          new BN(100),
+          6,
+          false
+        )
+        .accounts({
+          initializer: initializer.publicKey,
+          mintX: mint_x,
+          mintY: mint_y,
+          mintLp: mint_lp6,
+          vaultX: vault_x6_ata,
+          vaultY: vault_y6_ata,
+          vaultLp: vault_lp6_ata,
+          auth: auth6,
+          config: config6,
+          oracle: oracle6,
+          stats: stats6,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: web3.SystemProgram.programId,
+        })
+        .signers([initializer])
+        .rpc()
+        .then(confirmTx);
+
+      await program.methods
+        .deposit(
+          new BN(1),
+          new BN(10 * 10 ** 6),
+          new BN(10 * 10 ** 6),
+          new BN(Math.floor(new Date().getTime() / 1000) + 600)
+        )
+        .accounts({ ...accounts(), vaultLp: vault_lp6_ata })
+        .signers([initializer])
+        .rpc()
+        .then(confirmTx);
+
+      const pool = await program.account.config.fetch(config6);
+      assert.deepEqual(pool.curve, stableSwap);
+      assert.equal(pool.targetAmp.toNumber(), 100);
+    });
+
+    it("Swaps close to 1:1 at the peg", async () => {
+      const before = await getAccount(
+        connection,
+        initializer_y_ata.address,
+        commitment
+      );
+
+      await program.methods
+        .swap(
+          true,
+          new BN(1 * 10 ** 6),
+          new BN(1),
+          new BN(Math.floor(new Date().getTime() / 1000) + 600)
+        )
+        .accounts(accounts())
+        .signers([initializer])
+        .rpc()
+        .then(confirmTx);
+
+      // A constant product pool of the same size would pay out less than 0.91
+      const after = await getAccount(
+        connection,
+        initializer_y_ata.address,
+        commitment
+      );
+      assert.isTrue(Number(after.amount - before.amount) > 0.99 * 10 ** 6);
+    });
+
+    it("Ramp amp fails when it is too fast", async () => {
+      try {
+        await program.methods
+          .rampAmp(
+            new BN(200),
+            new BN(Math.floor(new Date().getTime() / 1000) + 600)
+          )
+          .accounts({ user: initializer.publicKey, config: config6 })
+          .signers([initializer])
+          .rpc();
+        assert.fail("ramp should need at least a day");
+      } catch (e) {
+        assert.equal(e.error.errorCode.code, "InvalidRamp");
+      }
+    });
+
+    it("Ramp amp", async () => {
+      const rampEnd = Math.floor(new Date().getTime() / 1000) + 2 * 86400;
+      await program.methods
+        .rampAmp(new BN(200), new BN(rampEnd))
+        .accounts({ user: initializer.publicKey, config: config6 })
+        .signers([initializer])
+        .rpc()
+        .then(confirmTx);
+
+      const pool = await program.account.config.fetch(config6);
+      assert.equal(pool.initialAmp.toNumber(), 100);
+      assert.equal(pool.targetAmp.toNumber(), 200);
+      assert.equal(pool.rampEnd.toNumber(), rampEnd);
+    });
+
+    it("Ramp amp fails on a constant product pool", async () => {
+      try {
+        await program.methods
+          .rampAmp(
+            new BN(200),
+            new BN(Math.floor(new Date().getTime() / 1000) + 2 * 86400)
+          )
+          .accounts({ user: initializer.publicKey, config })
+          .signers([initializer])
+          .rpc();
+        assert.fail("constant product pools have no amplification");
+      } catch (e) {
+        assert.equal(e.error.errorCode.code, "UnsupportedCurve");
+      }
+    });
+  });
+
+  describe("pair registry", () => {
+    let mint_lo: web3.PublicKey;
+    let mint_hi: web3.PublicKey;
+    let registered: BN;
+
+    const pdas = (seed: BN) => {
+      const [config] = web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("config"), seed.toBuffer().reverse()],
+        program.programId
+      );
+      const [auth] = web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("auth"), config.toBuffer()],
+        program.programId
+      );
+      const [oracle] = web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("oracle"), config.toBuffer()],
+        program.programId
+      );
+      const [stats] = web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("stats"), config.toBuffer()],
+        program.programId
+      );
+      const [mint_lp] = web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("lp"), config.toBuffer()],
+        program.programId
+      );
+      return { config, auth, oracle, stats, mint_lp };
+    };
+
+    const initializePool = (
+      seed: BN,
+      mintX: web3.PublicKey,
+      mintY: web3.PublicKey
+    ) => {
+      const { config, auth, oracle, stats, mint_lp } = pdas(seed);
+      return program.methods
+        .initialize(
+          seed,
+          30,
+          initializer.publicKey,
+          constantProduct,
+          new BN(0),
+          6,
+          false
+        )
+        .accounts({
+          initializer: initializer.publicKey,
+          mintX,
+          mintY,
+          mintLp: mint_lp,
+          vaultX: getAssociatedTokenAddressSync(mintX, auth, true),
+          vaultY: getAssociatedTokenAddressSync(mintY, auth, true),
+          vaultLp: getAssociatedTokenAddressSync(mint_lp, auth, true),
+          auth,
+          config,
+          oracle,
+          stats,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: web3.SystemProgram.programId,
+        })
+        .signers([initializer])
+        .rpc();
+    };
+
+    const registerPair = (
+      seed: BN,
+      mintX: web3.PublicKey,
+      mintY: web3.PublicKey
+    ) => {
+      const [pair] = web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("pair"), mintX.toBuffer(), mintY.toBuffer()],
+        program.programId
+      );
+      return program.methods
+        .registerPair()
+        .accounts({
+          user: initializer.publicKey,
+          mintX,
+          mintY,
+          config: pdas(seed).config,
+          pair,
+          systemProgram: web3.SystemProgram.programId,
+        })
+        .signers([initializer])
+        .rpc();
+    };
+
+    it("Create two mints in canonical order", async () => {
+      const a = await createMint(connection, user, user.publicKey, null, 6);
+      const b = await createMint(connection, user, user.publicKey, null, 6);
+      [mint_lo, mint_hi] =
+        Buffer.compare(a.toBuffer(), b.toBuffer()) < 0 ? [a, b] : [b, a];
+    });
+
+    it("Initialize fails with the same mint twice", async () => {
+      try {
+        await initializePool(new BN(randomBytes(8)), mint_lo, mint_lo);
+        assert.fail("a pool needs two different mints");
+      } catch (e) {
+        assert.equal(e.error.errorCode.code, "IdenticalMints");
+      }
+    });
+
+    const contestPair = (seed: BN, current: BN, signer: web3.Keypair) => {
+      const [pair] = web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("pair"), mint_lo.toBuffer(), mint_hi.toBuffer()],
+        program.programId
+      );
+      const { config, mint_lp } = pdas(seed);
+      const { config: currentConfig, mint_lp: currentMintLp } = pdas(current);
+      return program.methods
+        .contestPair()
+        .accounts({
+          user: signer.publicKey,
+          mintX: mint_lo,
+          mintY: mint_hi,
+          config,
+          mintLp: mint_lp,
+          current: currentConfig,
+          currentMintLp,
+          pair,
+        })
+        .signers([signer])
+        .rpc();
+    };
+
+    it("Register the canonical pool for a pair", async () => {
+      const seed = new BN(randomBytes(8));
+      registered = seed;
+      await initializePool(seed, mint_lo, mint_hi).then(confirmTx);
+      await registerPair(seed, mint_lo, mint_hi).then(confirmTx);
+
+      const [pair] = web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("pair"), mint_lo.toBuffer(), mint_hi.toBuffer()],
+        program.programId
+      );
+      const account = await program.account.pair.fetch(pair);
+      assert.isTrue(account.config.equals(pdas(seed).config));
+    });
+
+    it("Register fails for a pool with unsorted mints", async () => {
+      const seed = new BN(randomBytes(8));
+      await initializePool(seed, mint_hi, mint_lo).then(confirmTx);
+      try {
+        await registerPair(seed, mint_hi, mint_lo);
+        assert.fail("only sorted pools can be registered");
+      } catch (e) {
+        assert.equal(e.error.errorCode.code, "UnsortedMints");
+      }
+    });
+
+    it("Contest fails for a pool without liquidity", async () => {
+      const seed = new BN(randomBytes(8));
+      await initializePool(seed, mint_lo, mint_hi).then(confirmTx);
+      try {
+        await contestPair(seed, registered, user);
+        assert.fail("an empty pool can't take over the pair");
+      } catch (e) {
+        assert.equal(e.error.errorCode.code, "NoLiquidityInPool");
+      }
+    });
+
+    it("Anyone can move an unfunded pair to a funded pool", async () => {
+      const seed = new BN(randomBytes(8));
+      await initializePool(seed, mint_lo, mint_hi).then(confirmTx);
+      const { config, auth, oracle, stats, mint_lp } = pdas(seed);
+
+      const user_lo = await getOrCreateAssociatedTokenAccount(
+        connection,
+        user,
+        mint_lo,
+        user.publicKey
+      );
+      const user_hi = await getOrCreateAssociatedTokenAccount(
+        connection,
+        user,
+        mint_hi,
+        user.publicKey
+      );
+      await mintTo(connection, user, mint_lo, user_lo.address, user, 10 ** 9);
+      await mintTo(connection, user, mint_hi, user_hi.address, user, 10 ** 9);
+      await program.methods
+        .deposit(
+          new BN(1),
+          new BN(10 ** 9),
+          new BN(10 ** 9),
+          new BN(Math.floor(new Date().getTime() / 1000) + 600)
+        )
+        .accounts({
+          user: user.publicKey,
+          mintX: mint_lo,
+          mintY: mint_hi,
+          mintLp: mint_lp,
+          vaultX: getAssociatedTokenAddressSync(mint_lo, auth, true),
+          vaultY: getAssociatedTokenAddressSync(mint_hi, auth, true),
+          vaultLp: getAssociatedTokenAddressSync(mint_lp, auth, true),
+          userX: user_lo.address,
+          userY: user_hi.address,
+          userLp: getAssociatedTokenAddressSync(mint_lp, user.publicKey),
+          auth,
+          config,
+          oracle,
+          stats,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: web3.SystemProgram.programId,
+        })
+        .signers([user])
+        .rpc()
+        .then(confirmTx);
+
+      await contestPair(seed, registered, user).then(confirmTx);
+
+      const [pair] = web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("pair"), mint_lo.toBuffer(), mint_hi.toBuffer()],
+        program.programId
+      );
+      const account = await program.account.pair.fetch(pair);
+      assert.isTrue(account.config.equals(config));
+      registered = seed;
+    });
+
+    it("Contest fails while the registered pool holds liquidity", async () => {
+      const seed = new BN(randomBytes(8));
+      await initializePool(seed, mint_lo, mint_hi).then(confirmTx);
+      try {
+        await contestPair(seed, registered, user);
+        assert.fail("a funded pool keeps the pair");
+      } catch (e) {
+        assert.equal(e.error.errorCode.code, "PairInUse");
+      }
+    });
+  });
+
+  describe("lp mint", () => {
+    const metadataProgram = new web3.PublicKey(
+      "metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s"
+    );
+    const seed8 = new BN(randomBytes(8));
+    const [config8] = web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("config"), seed8.toBuffer().reverse()],
+      program.programId
+    );
+    const [auth8] = web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("auth"), config8.toBuffer()],
+      program.programId
+    );
+    const [oracle8] = web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("oracle"), config8.toBuffer()],
+      program.programId
+    );
+    const [stats8] = web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("stats"), config8.toBuffer()],
+      program.programId
+    );
+    const [mint_lp8] = web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("lp"), config8.toBuffer()],
+      program.programId
+    );
+    const metadata = (mint: web3.PublicKey) =>
+      web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("metadata"), metadataProgram.toBuffer(), mint.toBuffer()],
+        metadataProgram
+      )[0];
+
+    const initializeAccounts = () => ({
+      initializer: initializer.publicKey,
+      mintX: mint_x,
+      mintY: mint_y,
+      mintLp: mint_lp8,
+      vaultX: getAssociatedTokenAddressSync(mint_x, auth8, true),
+      vaultY: getAssociatedTokenAddressSync(mint_y, auth8, true),
+      vaultLp: getAssociatedTokenAddressSync(mint_lp8, auth8, true),
+      auth: auth8,
+      config: config8,
+      oracle: oracle8,
+      stats: stats8,
+      associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      systemProgram: web3.SystemProgram.programId,
+    });
+
+    it("Initialize fails with too many LP decimals", async () => {
+      try {
+        await program.methods
+          .initialize(
+            seed8,
+            30,
+            initializer.publicKey,
+            constantProduct,
+            new BN(0),
+            10,
+            false
+          )
+          .accounts(initializeAccounts())
+          .signers([initializer])
+          .rpc();
+        assert.fail("LP decimals are capped");
+      } catch (e) {
+        assert.equal(e.error.errorCode.code, "InvalidPrecision");
+      }
+    });
+
+    it("Initialize with 9 LP decimals", async () => {
+      await program.methods
+        .initialize(
+          seed8,
+          30,
+          initializer.publicKey,
+          constantProduct,
+          new BN(0),
+          9,
+          false
+        )
+        .accounts(initializeAccounts())
+        .signers([initializer])
+        .rpc()
+        .then(confirmTx);
+
+      const mint = await getMint(connection, mint_lp8, commitment);
+      assert.equal(mint.decimals, 9);
+      const account = await program.account.config.fetch(config8);
+      assert.equal(account.lpDecimals, 9);
+    });
+
+    it("Create LP metadata named after the pair", async () => {
+      await program.methods
+        .createLpMetadata()
+        .accounts({
+          payer: user.publicKey,
+          mintX: mint_x,
+          mintY: mint_y,
+          mintLp: mint_lp8,
+          metadataX: metadata(mint_x),
+          metadataY: metadata(mint_y),
+          metadataLp: metadata(mint_lp8),
+          auth: auth8,
+          config: config8,
+          metadataProgram,
+          systemProgram: web3.SystemProgram.programId,
+          rent: web3.SYSVAR_RENT_PUBKEY,
+        })
+        .signers([user])
+        .rpc()
+        .then(confirmTx);
+
+      // Neither test mint has metadata, so both fall back to their address
+      const info = await connection.getAccountInfo(metadata(mint_lp8));
+      const length = info.data.readUInt32LE(65);
+      const name = info.data
+        .subarray(69, 69 + length)
+        .toString()
+        .replace(/\0/g, "");
+      assert.equal(
+        name,
+        `${mint_x.toBase58().slice(0, 4)}-${mint_y.toBase58().slice(0, 4)} LP`
+      );
+    });
+  });
+
+  describe("farming", () => {
+    const [farm] = web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("farm"), config.toBuffer()],
+      program.programId
+    );
+    const [position] = web3.PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("stake"),
+        farm.toBuffer(),
+        initializer.publicKey.toBuffer(),
+      ],
+      program.programId
+    );
+    const farm_lp_ata = getAssociatedTokenAddressSync(mint_lp, farm, true);
+
+    let reward_mint: web3.PublicKey;
+    let initializer_reward_ata: Account;
+
+    const stakeAccounts = () => ({
+      user: initializer.publicKey,
+      mintLp: mint_lp,
+      rewardMint: reward_mint,
+      farm,
+      farmLp: farm_lp_ata,
+      farmReward: getAssociatedTokenAddressSync(reward_mint, farm, true),
+      userLp: initializer_lp_ata,
+      userReward: initializer_reward_ata.address,
+      position,
+      config,
+      associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      systemProgram: web3.SystemProgram.programId,
+    });
+
+    it("Create and fund a farm", async () => {
+      reward_mint = await createMint(connection, user, user.publicKey, null, 6);
+      initializer_reward_ata = await getOrCreateAssociatedTokenAccount(
+        connection,
+        initializer,
+        reward_mint,
+        initializer.publicKey
+      );
+      await mintTo(
+        connection,
+        user,
+        reward_mint,
+        initializer_reward_ata.address,
+        user.publicKey,
+        1e9
+      ).then(confirmTx);
+
+      await program.methods
+        .initFarm()
+        .accounts({
+          user: initializer.publicKey,
+          mintLp: mint_lp,
+          rewardMint: reward_mint,
+          farm,
+          farmLp: farm_lp_ata,
+          farmReward: getAssociatedTokenAddressSync(reward_mint, farm, true),
+          config,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: web3.SystemProgram.programId,
+        })
+        .signers([initializer])
+        .rpc()
+        .then(confirmTx);
+
+      await program.methods
+        .fundFarm(new BN(1e9), new BN(1e6))
+        .accounts({
+          user: initializer.publicKey,
+          rewardMint: reward_mint,
+          farm,
+          farmReward: getAssociatedTokenAddressSync(reward_mint, farm, true),
+          userReward: initializer_reward_ata.address,
+          config,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([initializer])
+        .rpc()
+        .then(confirmTx);
+
+      const account = await program.account.farm.fetch(farm);
+      assert.equal(account.rewardRemaining.toNumber(), 1e9);
+      assert.equal(account.rewardRate.toNumber(), 1e6);
+    });
+
+    it("Stake LP tokens", async () => {
+      await program.methods
+        .stakeLp(new BN(1_000))
+        .accounts(stakeAccounts())
+        .signers([initializer])
+        .rpc()
+        .then(confirmTx);
+
+      const staked = await getAccount(connection, farm_lp_ata, commitment);
+      assert.equal(Number(staked.amount), 1_000);
+      const account = await program.account.stakePosition.fetch(position);
+      assert.equal(account.amount.toNumber(), 1_000);
+    });
+
+    it("Claim rewards accrued while staked", async () => {
+      await new Promise((resolve) => setTimeout(resolve, 2000));
+      const before = await getAccount(
+        connection,
+        initializer_reward_ata.address,
+        commitment
+      );
+
+      await program.methods
+        .claimRewards()
+        .accounts(stakeAccounts())
+        .signers([initializer])
+        .rpc()
+        .then(confirmTx);
+
+      const after = await getAccount(
+        connection,
+        initializer_reward_ata.address,
+        commitment
+      );
+      assert.isTrue(after.amount > before.amount);
+      const account = await program.account.stakePosition.fetch(position);
+      assert.equal(account.pending.toNumber(), 0);
+    });
+
+    it("Unstake LP tokens", async () => {
+      await program.methods
+        .unstakeLp(new BN(1_000))
+        .accounts(stakeAccounts())
+        .signers([initializer])
+        .rpc()
+        .then(confirmTx);
+
+      const staked = await getAccount(connection, farm_lp_ata, commitment);
+      assert.equal(Number(staked.amount), 0);
+      const account = await program.account.farm.fetch(farm);
+      assert.equal(account.totalStaked.toNumber(), 0);
+    });
+
+    it("Unstake fails beyond the staked amount", async () => {
+      try {
+        await program.methods
+          .unstakeLp(new BN(1))
+          .accounts(stakeAccounts())
+          .signers([initializer])
+          .rpc();
+        assert.fail("nothing left to unstake");
+      } catch (e) {
+        assert.equal(e.error.errorCode.code, "InsufficientBalance");
+      }
+    });
+  });
+
+  describe("close_pool", () => {
+    const seed7 = new BN(randomBytes(8));
+    const [config7] = web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("config"), seed7.toBuffer().reverse()],
+      program.programId
+    );
+    const [auth7] = web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("auth"), config7.toBuffer()],
+      program.programId
+    );
+    const [oracle7] = web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("oracle"), config7.toBuffer()],
+      program.programId
+    );
+    const [stats7] = web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("stats"), config7.toBuffer()],
+      program.programId
+    );
+    const [mint_lp7] = web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("lp"), config7.toBuffer()],
+      program.programId
+    );
+    const vault_x7_ata = getAssociatedTokenAddressSync(mint_x, auth7, true);
+    const vault_y7_ata = getAssociatedTokenAddressSync(mint_y, auth7, true);
+    const vault_lp7_ata = getAssociatedTokenAddressSync(mint_lp7, auth7, true);
+    const initializer_lp7_ata = getAssociatedTokenAddressSync(
+      mint_lp7,
+      initializer.publicKey
+    );
+    const recipient = web3.Keypair.generate().publicKey;
+
+    const closeAccounts = () => ({
+      user: initializer.publicKey,
+      recipient,
+      mintX: mint_x,
+      mintY: mint_y,
+      mintLp: mint_lp7,
+      vaultX: vault_x7_ata,
+      vaultY: vault_y7_ata,
+      vaultLp: vault_lp7_ata,
+      treasuryX: initializer_x_ata.address,
+      treasuryY: initializer_y_ata.address,
+      auth: auth7,
+      config: config7,
+      oracle: oracle7,
+      stats: stats7,
+      tokenProgram: TOKEN_PROGRAM_ID,
+    });
+
+    it("Initialize and fund a pool to close", async () => {
+      await program.methods
+        .initialize(
+          seed7,
+          30,
+          initializer.publicKey,
+          constantProduct,
+          new BN(0),
+          6,
+          false
+        )
+        .accounts({
+          initializer: initializer.publicKey,
+          mintX: mint_x,
+          mintY: mint_y,
+          mintLp: mint_lp7,
+          vaultX: vault_x7_ata,
+          vaultY: vault_y7_ata,
+          vaultLp: vault_lp7_ata,
+          auth: auth7,
+          config: config7,
+          oracle: oracle7,
+          stats: stats7,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: web3.SystemProgram.programId,
+        })
+        .signers([initializer])
+        .rpc()
+        .then(confirmTx);
+
+      await program.methods
+        .deposit(
+          new BN(1),
+          new BN(1 * 10 ** 6),
+          new BN(1 * 10 ** 6),
+          new BN(Math.floor(new Date().getTime() / 1000) + 600)
+        )
+        .accounts({
+          user: initializer.publicKey,
+          mintX: mint_x,
+          mintY: mint_y,
+          mintLp: mint_lp7,
+          vaultX: vault_x7_ata,
+          vaultY: vault_y7_ata,
+          vaultLp: vault_lp7_ata,
+          userX: initializer_x_ata.address,
+          userY: initializer_y_ata.address,
+          userLp: initializer_lp7_ata,
+          auth: auth7,
+          config: config7,
+          oracle: oracle7,
+          stats: stats7,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: web3.SystemProgram.programId,
+        })
+        .signers([initializer])
+        .rpc()
+        .then(confirmTx);
+    });
+
+    it("Close fails while LP tokens are outstanding", async () => {
+      try {
+        await program.methods
+          .closePool()
+          .accounts(closeAccounts())
+          .signers([initializer])
+          .rpc();
+        assert.fail("pool still holds liquidity");
+      } catch (e) {
+        assert.equal(e.error.errorCode.code, "PoolNotEmpty");
+      }
+    });
+
+    it("Close fails for a non-authority", async () => {
+      try {
+        await program.methods
+          .closePool()
+          .accounts({
+            ...closeAccounts(),
+            user: user.publicKey,
+            treasuryX: user_x_ata.address,
+            treasuryY: user_y_ata.address,
+          })
+          .signers([user])
+          .rpc();
+        assert.fail("only the authority can close the pool");
+      } catch (e) {
+        assert.equal(e.error.errorCode.code, "InvalidAuthority");
+      }
+    });
+
+    it("Close fails with a treasury the authority doesn't own", async () => {
+      try {
+        await program.methods
+          .closePool()
+          .accounts({ ...closeAccounts(), treasuryX: user_x_ata.address })
+          .signers([initializer])
+          .rpc();
+        assert.fail("the reserves only go to the authority");
+      } catch (e) {
+        assert.equal(e.error.errorCode.code, "ConstraintAssociated");
+      }
+    });
+
+    it("Close an emptied pool and reclaim rent", async () => {
+      const lp = await getAccount(connection, initializer_lp7_ata, commitment);
+      const xBefore = await getAccount(
+        connection,
+        initializer_x_ata.address,
+        commitment
+      );
+      await program.methods
+        .withdraw(
+          new BN(lp.amount.toString()),
+          new BN(1),
+          new BN(1),
+          new BN(Math.floor(new Date().getTime() / 1000) + 600)
+        )
+        .accounts({
+          user: initializer.publicKey,
+          mintX: mint_x,
+          mintY: mint_y,
+          mintLp: mint_lp7,
+          vaultX: vault_x7_ata,
+          vaultY: vault_y7_ata,
+          userX: initializer_x_ata.address,
+          userY: initializer_y_ata.address,
+          userLp: initializer_lp7_ata,
+          auth: auth7,
+          config: config7,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: web3.SystemProgram.programId,
+        })
+        .signers([initializer])
+        .rpc()
+        .then(confirmTx);
+
+      await program.methods
+        .closePool()
+        .accounts(closeAccounts())
+        .signers([initializer])
+        .rpc()
+        .then(confirmTx);
+
+      assert.isNull(await connection.getAccountInfo(config7));
+      assert.isNull(await connection.getAccountInfo(oracle7));
+      assert.isNull(await connection.getAccountInfo(vault_x7_ata));
+      assert.isNull(await connection.getAccountInfo(vault_y7_ata));
+      assert.isNull(await connection.getAccountInfo(vault_lp7_ata));
+      assert.isTrue((await connection.getBalance(recipient)) > 0);
+
+      // The reserves behind the locked liquidity go to the authority, not burned
+      const x = await getAccount(
+        connection,
+        initializer_x_ata.address,
+        commitment
+      );
+      assert.isTrue(x.amount > xBefore.amount);
+    });
+  });
 });
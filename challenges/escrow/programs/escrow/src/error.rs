@@ -0,0 +1,17 @@
+use anchor_lang::prelude::*;
+
+#[error_code]
+pub enum EscrowError {
+    #[msg("Amount must be greater than zero")]
+    ZeroAmount,
+    #[msg("Minimum fill is larger than the escrow")]
+    InvalidMinFill,
+    #[msg("Fill is smaller than the escrow's minimum fill")]
+    FillTooSmall,
+    #[msg("Fill is larger than what is left in the escrow")]
+    FillTooLarge,
+    #[msg("Overflow")]
+    Overflow,
+    #[msg("Fill doesn't pay the maker anything")]
+    ZeroPayment,
+}
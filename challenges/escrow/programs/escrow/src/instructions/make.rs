@@ -4,7 +4,7 @@ use anchor_spl::{
     token_interface::{transfer_checked, Mint, TokenAccount, TokenInterface, TransferChecked},
 };
 
-use crate::state::Escrow;
+use crate::{error::EscrowError, state::Escrow};
 
 #[derive(Accounts)]
 #[instruction(seed: u64)]
@@ -47,12 +47,26 @@ pub struct Make<'info> {
 }
 
 impl<'info> Make<'info> {
-    pub fn make(&mut self, seed: u64, amount: u64, bumps: &MakeBumps) -> Result<()> {
+    pub fn make(
+        &mut self,
+        seed: u64,
+        amount: u64,
+        receive: u64,
+        min_fill: u64,
+        bumps: &MakeBumps,
+    ) -> Result<()> {
+        require!(amount > 0 && receive > 0, EscrowError::ZeroAmount);
+        require!(min_fill <= amount, EscrowError::InvalidMinFill);
+
         self.escrow.set_inner(Escrow {
             seed,
             mint_x: self.mint_x.to_account_info().key(),
             mint_y: self.mint_y.to_account_info().key(),
             amount,
+            receive,
+            min_fill,
+            initial_amount: amount,
+            initial_receive: receive,
             bump: bumps.escrow,
             maker: self.maker.to_account_info().key(),
         });
@@ -28,7 +28,6 @@ pub struct Take<'info> {
         has_one = mint_x,
         has_one = mint_y,
         has_one = maker,
-        close = maker,
         seeds = [b"escrow", escrow.maker.as_ref(), escrow.seed.to_le_bytes().as_ref()],
         bump = escrow.bump,
     )]
@@ -59,7 +58,7 @@ pub struct Take<'info> {
 }
 
 impl<'info> Take<'info> {
-    pub fn pay_back(&mut self) -> Result<()> {
+    pub fn pay_back(&mut self, owed: u64) -> Result<()> {
         let cpi_program = self.token_program.to_account_info();
 
         let cpi_accounts = TransferChecked {
@@ -71,9 +70,9 @@ impl<'info> Take<'info> {
 
         let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
 
-        transfer_checked(cpi_ctx, self.escrow.amount, self.mint_y.decimals)
+        transfer_checked(cpi_ctx, owed, self.mint_y.decimals)
     }
-    pub fn take(&mut self) -> Result<()> {
+    pub fn take(&mut self, amount: u64) -> Result<()> {
         let cpi_program = self.token_program.to_account_info();
 
         let cpi_accounts = TransferChecked {
@@ -90,7 +89,21 @@ impl<'info> Take<'info> {
         ]];
         let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, &signer_seeds);
 
-        transfer_checked(cpi_ctx, self.escrow.amount, self.mint_x.decimals)?;
+        transfer_checked(cpi_ctx, amount, self.mint_x.decimals)
+    }
+
+    // The vault and the escrow stay open until the last of the X is taken
+    pub fn close_if_filled(&mut self) -> Result<()> {
+        if !self.escrow.is_filled() {
+            return Ok(());
+        }
+
+        let signer_seeds: [&[&[u8]]; 1] = [&[
+            b"escrow",
+            self.maker.to_account_info().key.as_ref(),
+            &self.escrow.seed.to_le_bytes()[..],
+            &[self.escrow.bump],
+        ]];
         let close_accounts = CloseAccount {
             account: self.vault.to_account_info(),
             destination: self.maker.to_account_info(),
@@ -102,6 +115,8 @@ impl<'info> Take<'info> {
             &signer_seeds,
         );
 
-        close_account(cpi_ctx)
+        close_account(cpi_ctx)?;
+
+        self.escrow.close(self.maker.to_account_info())
     }
 }
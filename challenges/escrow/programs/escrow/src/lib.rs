@@ -1,5 +1,6 @@
 use anchor_lang::prelude::*;
 pub mod constants;
+pub mod error;
 pub mod instructions;
 pub mod state;
 
@@ -10,10 +11,17 @@ declare_id!("4hzLM36fkm3KKd9ywRxdqdUji62qDcGMCwiGt9i4TahQ");
 pub mod escrow {
     use super::*;
 
-    pub fn make_escrow(ctx: Context<Make>, seed: u64, amount: u64) -> Result<()> {
+    pub fn make_escrow(
+        ctx: Context<Make>,
+        seed: u64,
+        amount: u64,
+        receive: u64,
+        min_fill: u64,
+    ) -> Result<()> {
         // let bumps = ctx.bumps;
         ctx.accounts.transfer(amount)?;
-        ctx.accounts.make(seed, amount, &ctx.bumps)
+        ctx.accounts
+            .make(seed, amount, receive, min_fill, &ctx.bumps)
     }
 
     pub fn refund_escrow(ctx: Context<Refund>) -> Result<()> {
@@ -21,8 +29,10 @@ pub mod escrow {
         ctx.accounts.close_vault()
     }
 
-    pub fn take_escrow(ctx: Context<Take>) -> Result<()> {
-        ctx.accounts.pay_back()?;
-        ctx.accounts.take()
+    pub fn take_escrow(ctx: Context<Take>, amount: u64) -> Result<()> {
+        let owed = ctx.accounts.escrow.fill(amount)?;
+        ctx.accounts.pay_back(owed)?;
+        ctx.accounts.take(amount)?;
+        ctx.accounts.close_if_filled()
     }
 }
@@ -1,6 +1,9 @@
 use anchor_lang::prelude::*;
 
-use crate::constants::{DISCRIMINATOR_SIZE, PUBKEY_SIZE, U64_SIZE, U8_SIZE};
+use crate::{
+    constants::{DISCRIMINATOR_SIZE, PUBKEY_SIZE, U64_SIZE, U8_SIZE},
+    error::EscrowError,
+};
 
 #[account]
 pub struct Escrow {
@@ -8,7 +11,11 @@ pub struct Escrow {
     pub seed: u64,
     pub mint_x: Pubkey,
     pub mint_y: Pubkey,
-    pub amount: u64,
+    pub amount: u64,          // X left in the vault
+    pub receive: u64,         // Y still owed to the maker for the X left
+    pub min_fill: u64,        // Smallest amount of X a taker can fill, except for the remainder
+    pub initial_amount: u64,  // X the escrow was made with
+    pub initial_receive: u64, // Y asked for all of it, which prices every fill
     pub bump: u8,
 }
 
@@ -19,5 +26,39 @@ impl Space for Escrow {
         + PUBKEY_SIZE
         + PUBKEY_SIZE
         + U64_SIZE
+        + U64_SIZE
+        + U64_SIZE
+        + U64_SIZE
+        + U64_SIZE
         + U8_SIZE;
 }
+
+impl Escrow {
+    // Takes `amount` of X off the escrow and returns the Y owed for it. Everything
+    // filled so far is priced at the maker's original ratio, rounded up so the maker
+    // is never paid less than their price, and earlier fills pay their part of that
+    pub fn fill(&mut self, amount: u64) -> Result<u64> {
+        require!(amount > 0, EscrowError::ZeroAmount);
+        require!(amount <= self.amount, EscrowError::FillTooLarge);
+        require!(
+            amount >= self.min_fill || amount == self.amount,
+            EscrowError::FillTooSmall
+        );
+
+        let (total, price) = (self.initial_amount as u128, self.initial_receive as u128);
+        let filled = total - self.amount as u128 + amount as u128;
+        let paid = price - self.receive as u128;
+        let owed = (filled * price + total - 1) / total - paid;
+        // Rounding already paid for this X, so taking it would be free
+        require!(owed > 0, EscrowError::ZeroPayment);
+        let owed = u64::try_from(owed).map_err(|_| EscrowError::Overflow)?;
+
+        self.amount -= amount;
+        self.receive -= owed;
+        Ok(owed)
+    }
+
+    pub fn is_filled(&self) -> bool {
+        self.amount == 0
+    }
+}
@@ -15,9 +15,11 @@ import {
   createAssociatedTokenAccountIdempotentInstruction,
   createInitializeMint2Instruction,
   createMintToInstruction,
+  getAccount,
   getAssociatedTokenAddressSync,
   getMinimumBalanceForRentExemptMint,
 } from "@solana/spl-token";
+import { assert } from "chai";
 
 anchor.setProvider(anchor.AnchorProvider.env());
 
@@ -135,7 +137,12 @@ describe("escrow", () => {
 
   it("Make", async () => {
     await program.methods
-      .makeEscrow(seed, new anchor.BN(100e6))
+      .makeEscrow(
+        seed,
+        new anchor.BN(100e6),
+        new anchor.BN(200e6),
+        new anchor.BN(10e6)
+      )
       .accounts({
         maker: maker.publicKey,
         escrow,
@@ -172,25 +179,151 @@ describe("escrow", () => {
       .then(log);
   });
 
-  it("Take escrow", async () => {
+  const takeAccounts = {
+    taker: taker.publicKey,
+    maker: maker.publicKey,
+    escrow,
+    vault,
+    mintX: mintX.publicKey,
+    mintY: mintY.publicKey,
+    takerAtaX: takerAtaX,
+    takerAtaY: takerAtaY,
+    makerAtaY: makerAtaY,
+    associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+    tokenProgram: TOKEN_PROGRAM_ID,
+    systemProgram: SystemProgram.programId,
+  };
+
+  it("Take fails below the minimum fill", async () => {
+    try {
+      await program.methods
+        .takeEscrow(new anchor.BN(5e6))
+        .accounts(takeAccounts)
+        .signers([taker])
+        .rpc();
+      assert.fail("fill is below the minimum");
+    } catch (e) {
+      assert.equal(e.error.errorCode.code, "FillTooSmall");
+    }
+  });
+
+  it("Take part of the escrow", async () => {
+    await program.methods
+      .takeEscrow(new anchor.BN(40e6))
+      .accounts(takeAccounts)
+      .signers([taker])
+      .rpc()
+      .then(log);
+
+    // 40% of the X pays 40% of the Y
+    const paid = await getAccount(connection, makerAtaY);
+    assert.equal(paid.amount.toString(), "80000000");
+    const left = await program.account.escrow.fetch(escrow);
+    assert.equal(left.amount.toString(), "60000000");
+    assert.equal(left.receive.toString(), "120000000");
+    const held = await getAccount(connection, vault);
+    assert.equal(held.amount.toString(), "60000000");
+  });
+
+  it("Take fails for more than is left", async () => {
+    try {
+      await program.methods
+        .takeEscrow(new anchor.BN(61e6))
+        .accounts(takeAccounts)
+        .signers([taker])
+        .rpc();
+      assert.fail("fill is larger than the escrow");
+    } catch (e) {
+      assert.equal(e.error.errorCode.code, "FillTooLarge");
+    }
+  });
+
+  it("Take the rest of the escrow", async () => {
     await program.methods
-      .takeEscrow()
+      .takeEscrow(new anchor.BN(60e6))
+      .accounts(takeAccounts)
+      .signers([taker])
+      .rpc()
+      .then(log);
+
+    const paid = await getAccount(connection, makerAtaY);
+    assert.equal(paid.amount.toString(), "200000000");
+    const taken = await getAccount(connection, takerAtaX);
+    assert.equal(taken.amount.toString(), "100000000");
+    assert.isNull(await connection.getAccountInfo(escrow));
+    assert.isNull(await connection.getAccountInfo(vault));
+  });
+
+  // 10 X for 1 Y: the first fill rounds up to the whole price
+  const cheapSeed = new anchor.BN(2);
+  const [cheapEscrow] = PublicKey.findProgramAddressSync(
+    [
+      Buffer.from("escrow"),
+      maker.publicKey.toBuffer(),
+      cheapSeed.toBuffer("le", 8),
+    ],
+    program.programId
+  );
+  const cheapVault = getAssociatedTokenAddressSync(
+    mintX.publicKey,
+    cheapEscrow,
+    true
+  );
+  const cheapTakeAccounts = {
+    ...takeAccounts,
+    escrow: cheapEscrow,
+    vault: cheapVault,
+  };
+
+  it("Make a low-priced escrow", async () => {
+    await program.methods
+      .makeEscrow(
+        cheapSeed,
+        new anchor.BN(10),
+        new anchor.BN(1),
+        new anchor.BN(1)
+      )
       .accounts({
-        taker: taker.publicKey,
         maker: maker.publicKey,
-        escrow,
-        vault,
+        escrow: cheapEscrow,
         mintX: mintX.publicKey,
         mintY: mintY.publicKey,
-        takerAtaX: takerAtaX,
-        takerAtaY: takerAtaY,
+        vault: cheapVault,
+        makerAtaX: makerAtaX,
         makerAtaY: makerAtaY,
         associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
         tokenProgram: TOKEN_PROGRAM_ID,
         systemProgram: SystemProgram.programId,
       })
+      .signers([maker])
+      .rpc()
+      .then(log);
+  });
+
+  it("Fills are priced from the original ratio", async () => {
+    await program.methods
+      .takeEscrow(new anchor.BN(1))
+      .accounts(cheapTakeAccounts)
       .signers([taker])
       .rpc()
       .then(log);
+
+    const paid = await getAccount(connection, makerAtaY);
+    assert.equal(paid.amount.toString(), "200000001");
+    const left = await program.account.escrow.fetch(cheapEscrow);
+    assert.equal(left.amount.toString(), "9");
+    assert.equal(left.receive.toString(), "0");
+
+    // The maker is paid in full, so the X left can't be taken for free
+    try {
+      await program.methods
+        .takeEscrow(new anchor.BN(1))
+        .accounts(cheapTakeAccounts)
+        .signers([taker])
+        .rpc();
+      assert.fail("fill pays nothing");
+    } catch (e) {
+      assert.equal(e.error.errorCode.code, "ZeroPayment");
+    }
   });
 });